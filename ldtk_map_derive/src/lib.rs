@@ -0,0 +1,241 @@
+//! Derive macros for the `LdtkEntity` and `LdtkLevelFields` traits of ldtk_map.
+//!
+//! Enable the `derive` feature of ldtk_map instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, LitStr};
+
+/// Implements `ldtk_map::LdtkEntity`, reading every field of the struct from the entity.
+///
+/// Fields read the LDtk field of the same name, `#[ldtk(field = "...")]` picks another one.
+/// `#[ldtk(name)]`, `#[ldtk(tag)]`, `#[ldtk(iid)]`, `#[ldtk(grid_xy)]` and `#[ldtk(world_px)]`
+/// read those parts of the entity instead.
+#[proc_macro_derive(LdtkEntity, attributes(ldtk))]
+pub fn derive_ldtk_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, Target::Entity)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements `ldtk_map::LdtkLevelFields`, reading every field of the struct from the level.
+///
+/// Fields read the LDtk field of the same name, `#[ldtk(field = "...")]` picks another one.
+/// `#[ldtk(name)]`, `#[ldtk(iid)]` and `#[ldtk(world_px)]` read those parts of the level instead.
+#[proc_macro_derive(LdtkLevelFields, attributes(ldtk))]
+pub fn derive_ldtk_level_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, Target::Level)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Entity,
+    Level,
+}
+
+/// Where a field of the struct is read from
+enum Source {
+    Field(String),
+    Name,
+    Tag,
+    Iid,
+    GridXy,
+    WorldPx,
+}
+
+fn expand(input: &DeriveInput, target: Target) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "LdtkEntity and LdtkLevelFields can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "LdtkEntity and LdtkLevelFields need a struct with named fields",
+        ));
+    };
+
+    let source = match target {
+        Target::Entity => quote!(spawn),
+        Target::Level => quote!(level),
+    };
+    let mut values = vec![];
+    for field in fields.named.iter() {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("named fields have an identifier");
+        let value = match field_source(field, target)? {
+            Source::Field(identifier) => quote! {
+                ::ldtk_map::__field(#source.fields(), #identifier, #source.name())?
+            },
+            Source::Name => quote!(#source.name().to_string()),
+            Source::Tag => quote!(#source.tag().map(|tag| tag.to_string())),
+            Source::Iid => quote!(#source.iid().map(|iid| iid.to_string())),
+            Source::GridXy => quote!(#source.grid_xy()),
+            Source::WorldPx => quote!(#source.world_px()),
+        };
+        values.push(quote!(#ident: #value));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(match target {
+        Target::Entity => quote! {
+            impl #impl_generics ::ldtk_map::LdtkEntity for #name #ty_generics #where_clause {
+                fn from_spawn(spawn: &::ldtk_map::prelude::EntitySpawn) -> ::std::result::Result<Self, ::std::string::String> {
+                    ::std::result::Result::Ok(Self { #(#values),* })
+                }
+            }
+        },
+        Target::Level => quote! {
+            impl #impl_generics ::ldtk_map::LdtkLevelFields for #name #ty_generics #where_clause {
+                fn from_level(level: &::ldtk_map::prelude::DesignLevel) -> ::std::result::Result<Self, ::std::string::String> {
+                    ::std::result::Result::Ok(Self { #(#values),* })
+                }
+            }
+        },
+    })
+}
+
+/// Reads the `#[ldtk(...)]` attribute of a field, defaulting to the LDtk field of the same name
+fn field_source(field: &Field, target: Target) -> syn::Result<Source> {
+    let ident = field
+        .ident
+        .as_ref()
+        .expect("named fields have an identifier");
+    let mut source = Source::Field(ident.to_string().trim_start_matches("r#").to_string());
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("ldtk"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let path = &meta.path;
+            source = if path.is_ident("field") {
+                Source::Field(meta.value()?.parse::<LitStr>()?.value())
+            } else if path.is_ident("name") {
+                Source::Name
+            } else if path.is_ident("iid") {
+                Source::Iid
+            } else if path.is_ident("world_px") {
+                Source::WorldPx
+            } else if path.is_ident("tag") && target == Target::Entity {
+                Source::Tag
+            } else if path.is_ident("grid_xy") && target == Target::Entity {
+                Source::GridXy
+            } else {
+                let expected = match target {
+                    Target::Entity => "field, name, tag, iid, grid_xy or world_px",
+                    Target::Level => "field, name, iid or world_px",
+                };
+                return Err(meta.error(format!("unknown ldtk attribute, expected {}", expected)));
+            };
+            Ok(())
+        })?;
+    }
+    Ok(source)
+}
+
+// shared with the tests of ldtk_map, which use more of it
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "../../src/test_support/temp.rs"]
+mod temp;
+
+#[cfg(test)]
+mod tests {
+    use ldtk_map::{prelude::*, LdtkEntity, LdtkLevelFields};
+    use serde_json::json;
+
+    use crate::temp::patched;
+
+    #[derive(LdtkEntity, Debug, PartialEq)]
+    struct Monster {
+        #[ldtk(name)]
+        name: String,
+        #[ldtk(grid_xy)]
+        xy: (usize, usize),
+        #[ldtk(world_px)]
+        world_px: (i64, i64),
+        #[ldtk(field = "hp")]
+        health: i64,
+        speed: Option<f32>,
+        patrol: Vec<(i64, i64)>,
+    }
+
+    #[derive(LdtkLevelFields, Debug, PartialEq)]
+    struct LevelInfo {
+        #[ldtk(name)]
+        name: String,
+        music: String,
+        #[ldtk(field = "darkness")]
+        dark: bool,
+    }
+
+    fn load_project(name: &str) -> DesignMap {
+        let path = patched(
+            "../tests/testmaps/entities.ldtk",
+            &format!("derive_{}.ldtk", name),
+            |project| {
+                project["levels"][0]["fieldInstances"] = json!([
+                    { "__identifier": "music", "__type": "String", "__value": "forest.ogg" },
+                    { "__identifier": "darkness", "__type": "Bool", "__value": true }
+                ]);
+                let entities = &mut project["levels"][0]["layerInstances"][1]["entityInstances"];
+                entities[0]["fieldInstances"] = json!([
+                    { "__identifier": "hp", "__type": "Int", "__value": 25 },
+                    { "__identifier": "speed", "__type": "Float", "__value": null },
+                    { "__identifier": "patrol", "__type": "Array<Point>", "__value": [{ "cx": 1, "cy": 2 }] }
+                ]);
+            },
+        );
+        DesignMap::load(&path)
+    }
+
+    #[test]
+    fn test_derive_entity() {
+        let map = load_project("entity");
+        let registry =
+            EntityRegistry::new().register("Monster1", |monsters: &mut Vec<_>, spawn| {
+                monsters.push(Monster::from_spawn(spawn));
+            });
+        let mut monsters = vec![];
+        registry.spawn_level(&map.levels()["Level_0"], &mut monsters);
+
+        assert_eq!(
+            monsters[0],
+            Ok(Monster {
+                name: "Monster1".to_string(),
+                xy: (0, 0),
+                world_px: (8, 8),
+                health: 25,
+                speed: None,
+                patrol: vec![(1, 2)],
+            })
+        );
+        assert_eq!(monsters[1], Err("Monster1 has no field hp".to_string()));
+    }
+
+    #[test]
+    fn test_derive_level_fields() {
+        let map = load_project("level");
+        let info = LevelInfo::from_level(&map.levels()["Level_0"]).unwrap();
+        assert_eq!(
+            info,
+            LevelInfo {
+                name: "Level_0".to_string(),
+                music: "forest.ogg".to_string(),
+                dark: true,
+            }
+        );
+    }
+}
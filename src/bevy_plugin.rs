@@ -0,0 +1,371 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    image::{TextureAtlas, TextureAtlasLayout},
+    prelude::*,
+};
+use serde_json::Value;
+
+use crate::{
+    compression::decompress,
+    design_map::{DesignLevel, DesignMap, TileContents},
+    ldtk_helpers::{try_parse_raw_world, ATLAS_WIDTH},
+    validation::{validate_world, Severity},
+};
+
+/// Adds `DesignMap` as an asset loaded from .ldtk files and spawns the levels of `LdtkLevel` entities.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use ldtk_map::prelude::*;
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands.spawn(LdtkLevel::new(asset_server.load("maps/world.ldtk"), "Level_0"));
+/// }
+///
+/// App::new()
+///     .add_plugins((DefaultPlugins, LdtkMapPlugin))
+///     .add_systems(Startup, setup)
+///     .run();
+/// ```
+pub struct LdtkMapPlugin;
+
+impl Plugin for LdtkMapPlugin {
+    fn build(&self, app: &mut App) {
+        // sprites need both, they are only missing when the render plugins are not added
+        if !app.world().contains_resource::<Assets<Image>>() {
+            app.init_asset::<Image>();
+        }
+        if !app
+            .world()
+            .contains_resource::<Assets<TextureAtlasLayout>>()
+        {
+            app.init_asset::<TextureAtlasLayout>();
+        }
+        app.init_asset::<DesignMap>()
+            .init_asset_loader::<DesignMapLoader>()
+            .add_systems(Update, spawn_levels);
+    }
+}
+
+/// Loads .ldtk files into a `DesignMap`, levels saved in their own files included
+#[derive(Default)]
+pub struct DesignMapLoader;
+
+impl AssetLoader for DesignMapLoader {
+    type Asset = DesignMap;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<DesignMap, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let project_path = load_context.path().to_path_buf();
+        let contents = decompress(&bytes, &project_path.display().to_string())?;
+
+        // external levels are read up front as the parsing itself is not async
+        let mut external_levels = vec![];
+        let project: Value = serde_json::from_str(&contents)?;
+        let rel_paths = project["levels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|level| level["externalRelPath"].as_str());
+        for rel_path in rel_paths {
+            let path = relative_to(&project_path, rel_path);
+            let level = load_context.read_asset_bytes(path).await?;
+            external_levels.push((rel_path.to_string(), decompress(&level, rel_path)?));
+        }
+
+        let ldtk_world =
+            try_parse_raw_world(&contents, &project_path.display().to_string(), |rel_path| {
+                external_levels
+                    .iter()
+                    .find(|(path, _)| path == rel_path)
+                    .map(|(_, level)| level.clone())
+                    .ok_or_else(|| format!("{} was not read", rel_path))
+            })?;
+
+        // a broken map fails its asset instead of panicking in the loader
+        let errors: Vec<String> = validate_world(&ldtk_world)
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+            .map(|diagnostic| diagnostic.to_string())
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors.join("\n").into());
+        }
+        Ok(DesignMap::from_raw_world(&ldtk_world))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk", "ldtk.gz", "ldtk.zst"]
+    }
+}
+
+/// Spawns the level named `name` of a `DesignMap` as children of the entity, once the map is loaded.
+///
+/// Levels are placed at their world position with the y axis flipped, so the level keeps
+/// the layout it has in LDtk. Every tile holding a sprite, an IntGrid value or an entity
+/// becomes a child with its `GridCoords`.
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct LdtkLevel {
+    map: Handle<DesignMap>,
+    name: String,
+}
+
+impl LdtkLevel {
+    /// The level `name` of the map behind `map`
+    pub fn new(map: Handle<DesignMap>, name: impl ToString) -> Self {
+        Self {
+            map,
+            name: name.to_string(),
+        }
+    }
+
+    /// The handle of the map the level is in
+    pub fn map(&self) -> &Handle<DesignMap> {
+        &self.map
+    }
+
+    /// The name of the level
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+}
+
+/// Added to `LdtkLevel` entities once their tiles are spawned
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LdtkLevelSpawned;
+
+/// The x and y of a tile in its level, as used by `DesignLevel::get`
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridCoords {
+    /// The column of the tile
+    pub x: usize,
+    /// The row of the tile
+    pub y: usize,
+}
+
+/// The IntGrid value painted on a tile of the Values layer
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct IntGridCell {
+    /// The value, as in `TileContents::value`
+    pub value: usize,
+    /// The name given to the value in LDtk
+    pub identifier: Option<String>,
+}
+
+/// Marks a tile holding an entity of the Entities layer
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct EntityMarker {
+    /// The name of the entity, as in `TileContents::entity_name`
+    pub name: String,
+    /// The unique id LDtk gave the entity instance
+    pub iid: Option<String>,
+}
+
+fn spawn_levels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    maps: Res<Assets<DesignMap>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    levels: Query<(Entity, &LdtkLevel), Without<LdtkLevelSpawned>>,
+) {
+    for (entity, ldtk_level) in levels.iter() {
+        let Some(map) = maps.get(&ldtk_level.map) else {
+            continue;
+        };
+        let Some(level) = map.levels().get(&ldtk_level.name) else {
+            bevy::log::error!("{} is not a level of the map", ldtk_level.name);
+            commands.entity(entity).insert(LdtkLevelSpawned);
+            continue;
+        };
+
+        let tileset = level.tileset();
+        let atlas_image: Option<Handle<Image>> = tileset.rel_path().and_then(|rel_path| {
+            let map_path = asset_server.get_path(&ldtk_level.map)?;
+            Some(asset_server.load(relative_to(map_path.path(), rel_path)))
+        });
+        let tile_size = tileset.tile_grid_size() as u32;
+        let layout = layouts.add(TextureAtlasLayout::from_grid(
+            UVec2::splat(tile_size),
+            tileset.columns() as u32,
+            tileset.rows() as u32,
+            Some(UVec2::splat(tileset.spacing() as u32)),
+            Some(UVec2::splat(tileset.padding() as u32)),
+        ));
+
+        let (world_x, world_y) = level.world_px();
+        commands
+            .entity(entity)
+            .insert((
+                Transform::from_xyz(world_x as f32, -world_y as f32, 0.0),
+                LdtkLevelSpawned,
+            ))
+            .with_children(|parent| {
+                for (xy, tile) in level.iter_coords() {
+                    spawn_tile(parent, level, xy, tile, atlas_image.as_ref(), &layout);
+                }
+            });
+    }
+}
+
+fn spawn_tile(
+    parent: &mut ChildSpawnerCommands,
+    level: &DesignLevel,
+    (x, y): (usize, usize),
+    tile: &TileContents,
+    atlas_image: Option<&Handle<Image>>,
+    layout: &Handle<TextureAtlasLayout>,
+) {
+    if !tile.has_tile() && tile.value() == 0 && tile.entity_name().is_none() {
+        return;
+    }
+
+    let grid_size = level.grid_size_px() as f32;
+    let mut cell = parent.spawn((
+        GridCoords { x, y },
+        Transform::from_xyz(
+            (x as f32 + 0.5) * grid_size,
+            -(y as f32 + 0.5) * grid_size,
+            0.0,
+        ),
+    ));
+    if let (true, Some(image)) = (tile.has_tile(), atlas_image) {
+        // atlas indexes are always 16 wide, bevy counts with the real width of the atlas
+        let columns = level.tileset().columns().max(1);
+        let atlas_index = tile.atlas_index();
+        let mut sprite = Sprite::from_atlas_image(
+            image.clone(),
+            TextureAtlas {
+                layout: layout.clone(),
+                index: atlas_index % ATLAS_WIDTH + atlas_index / ATLAS_WIDTH * columns,
+            },
+        );
+        sprite.flip_x = tile.flip_x();
+        sprite.flip_y = tile.flip_y();
+        cell.insert(sprite);
+    }
+    if tile.value() > 0 {
+        let identifier = level
+            .int_grid_values()
+            .iter()
+            .find(|value| value.value() == tile.value())
+            .and_then(|value| value.identifier())
+            .map(|identifier| identifier.to_string());
+        cell.insert(IntGridCell {
+            value: tile.value(),
+            identifier,
+        });
+    }
+    if let Some(name) = tile.entity_name() {
+        cell.insert(EntityMarker {
+            name: name.to_string(),
+            iid: tile.entity_iid().map(|iid| iid.to_string()),
+        });
+    }
+}
+
+/// The path of `rel_path`, given relative to the file at `path`
+fn relative_to(path: &Path, rel_path: &str) -> PathBuf {
+    path.parent().unwrap_or(Path::new("")).join(rel_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::LoadState, prelude::*};
+
+    use super::{
+        EntityMarker, GridCoords, IntGridCell, LdtkLevel, LdtkLevelSpawned, LdtkMapPlugin,
+    };
+    use crate::{
+        design_map::DesignMap,
+        project_builder::LevelBuilder,
+        test_support::{forest_project, TempPath},
+    };
+
+    #[test]
+    fn test_spawn_level_headless() {
+        let dir = TempPath::dir("bevy");
+        let path = dir.join("world.ldtk");
+        forest_project(8, &["wall"])
+            .level(
+                LevelBuilder::new("Level_0", (32, 16))
+                    .tile("Ground", (0, 0), 0)
+                    .tile("Ground", (1, 0), 0)
+                    .entity("Entities", (2, 0), "Monster1")
+                    .entity("Entities", (3, 1), "Monster1")
+                    .value("Values", (0, 1), 1),
+            )
+            .save(&path);
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string(),
+                ..default()
+            },
+            LdtkMapPlugin,
+        ));
+        let map: Handle<DesignMap> = app.world().resource::<AssetServer>().load("world.ldtk");
+        let level = app
+            .world_mut()
+            .spawn(LdtkLevel::new(map.clone(), "Level_0"))
+            .id();
+
+        for _ in 0..1000 {
+            app.update();
+            if app.world().get::<LdtkLevelSpawned>(level).is_some() {
+                break;
+            }
+            let state = app.world().resource::<AssetServer>().load_state(&map);
+            assert!(!matches!(state, LoadState::Failed(_)), "{:?}", state);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(app.world().get::<LdtkLevelSpawned>(level).is_some());
+
+        let expected = DesignMap::load(&path);
+        let expected = &expected.levels()["Level_0"];
+        let world = app.world_mut();
+        let sprites = world
+            .query_filtered::<&GridCoords, With<Sprite>>()
+            .iter(world)
+            .count();
+        assert_eq!(
+            sprites,
+            expected
+                .iter_coords()
+                .filter(|(_, tile)| tile.has_tile())
+                .count()
+        );
+        let markers: Vec<_> = world
+            .query::<(&GridCoords, &EntityMarker)>()
+            .iter(world)
+            .map(|(coords, marker)| (coords.x, coords.y, marker.name.clone()))
+            .collect();
+        assert_eq!(markers.len(), 2);
+        for (x, y, name) in markers {
+            assert_eq!(
+                expected.get(x, y).unwrap().entity_name(),
+                Some(name.as_str())
+            );
+        }
+        let values = world.query::<&IntGridCell>().iter(world).count();
+        assert_eq!(
+            values,
+            expected
+                .iter_coords()
+                .filter(|(_, tile)| tile.value() > 0)
+                .count()
+        );
+    }
+}
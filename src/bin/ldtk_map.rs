@@ -0,0 +1,281 @@
+//! Command line tool for looking inside, checking and converting LDtk maps without writing a program.
+
+use std::{collections::BTreeSet, fs, path::Path, process::ExitCode};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ldtk_map::{bake, diff, prelude::*, validate, Severity};
+
+#[derive(Parser)]
+#[command(name = "ldtk_map", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the levels of a map with their sizes and tilesets
+    Info {
+        /// The ldtk file to read
+        map: String,
+    },
+    /// Check a map against the conventions of ldtk_map, exits with 1 when problems are found
+    Validate {
+        /// The ldtk file to check
+        map: String,
+        /// Also fail on warnings
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Print an ASCII view of a level
+    Dump {
+        /// The ldtk file to read
+        map: String,
+        /// The name of the level to print
+        level: String,
+        /// What to show for each tile
+        #[arg(long, value_enum, default_value_t = DumpLayer::All)]
+        layer: DumpLayer,
+    },
+    /// Summarize what changed between two versions of a map
+    Diff {
+        /// The ldtk file before the change
+        old: String,
+        /// The ldtk file after the change
+        new: String,
+    },
+    /// Convert a map into another format, chosen by the extension of the output
+    Convert {
+        /// The ldtk file to read
+        map: String,
+        /// Where to write the converted map
+        output: String,
+        /// Only convert this level, formats holding a single level otherwise write one file per level
+        #[arg(long)]
+        level: Option<String>,
+    },
+    /// Write a map and its separate level files into a single compact file, ready to embed
+    Bake {
+        /// The ldtk file to read
+        map: String,
+        /// Where to write the baked map
+        output: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpLayer {
+    /// Entities over values over tiles
+    All,
+    /// Atlas indexes of the Ground layer
+    Tiles,
+    /// IntGrid values of the Values layer
+    Values,
+    /// First letter of the entities' names
+    Entities,
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Info { map } => info(&map),
+        Command::Validate { map, deny_warnings } => check(&map, deny_warnings),
+        Command::Dump { map, level, layer } => dump(&map, &level, layer),
+        Command::Diff { old, new } => compare(&old, &new),
+        Command::Convert { map, output, level } => convert(&map, &output, level.as_deref()),
+        Command::Bake { map, output } => bake_into(&map, &output),
+    }
+}
+
+fn bake_into(map: &str, output: &str) -> ExitCode {
+    if let Err(code) = load(map) {
+        return code;
+    }
+    bake(map, output);
+    println!("Wrote {}", output);
+    ExitCode::SUCCESS
+}
+
+/// Loads the map only once it is known to load, so problems are reported instead of panicking
+fn load(map: &str) -> Result<DesignMap, ExitCode> {
+    let errors: Vec<_> = validate(map)
+        .into_iter()
+        .filter(|d| d.severity() == Severity::Error)
+        .collect();
+    if errors.is_empty() {
+        return Ok(DesignMap::load(map));
+    }
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    Err(ExitCode::FAILURE)
+}
+
+fn info(map: &str) -> ExitCode {
+    let design_map = match load(map) {
+        Ok(design_map) => design_map,
+        Err(code) => return code,
+    };
+
+    let mut levels: Vec<_> = design_map.levels().values().collect();
+    levels.sort_by(|a, b| a.name().cmp(b.name()));
+    let tilesets: BTreeSet<_> = levels.iter().map(|level| level.tileset_name()).collect();
+
+    println!("{} levels", levels.len());
+    for level in levels {
+        println!(
+            "  {:<20} {:>4} x {:<4} {:>3}px  world ({}, {})  tileset {}",
+            level.name(),
+            level.width(),
+            level.height(),
+            level.grid_size_px(),
+            level.world_tile_x(),
+            level.world_tile_y(),
+            level.tileset_name()
+        );
+    }
+    println!("{} tilesets", tilesets.len());
+    for tileset in tilesets {
+        println!("  {}", tileset);
+    }
+    ExitCode::SUCCESS
+}
+
+fn check(map: &str, deny_warnings: bool) -> ExitCode {
+    let diagnostics = validate(map);
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+
+    let failed = diagnostics
+        .iter()
+        .any(|d| deny_warnings || d.severity() == Severity::Error);
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        println!("{} is valid", map);
+        ExitCode::SUCCESS
+    }
+}
+
+fn dump(map: &str, level_name: &str, layer: DumpLayer) -> ExitCode {
+    let design_map = match load(map) {
+        Ok(design_map) => design_map,
+        Err(code) => return code,
+    };
+    let Some(level) = design_map.levels().get(level_name) else {
+        eprintln!("{} does not have a level named {}", map, level_name);
+        return ExitCode::FAILURE;
+    };
+
+    for row in level.rows() {
+        let line: String = row.iter().map(|tile| tile_char(tile, layer)).collect();
+        println!("{}", line);
+    }
+    ExitCode::SUCCESS
+}
+
+fn tile_char(tile: &TileContents, layer: DumpLayer) -> char {
+    let base36 = |n: usize| char::from_digit((n % 36) as u32, 36).unwrap_or('?');
+    let entity = tile.entity_name().and_then(|name| name.chars().next());
+    let value = (tile.value() != 0).then(|| base36(tile.value()));
+    let atlas = tile.has_tile().then(|| base36(tile.atlas_index()));
+
+    match layer {
+        DumpLayer::All => entity.or(value).or(tile.has_tile().then_some('#')),
+        DumpLayer::Tiles => atlas,
+        DumpLayer::Values => value,
+        DumpLayer::Entities => entity,
+    }
+    .unwrap_or('.')
+}
+
+fn compare(old: &str, new: &str) -> ExitCode {
+    let (old_map, new_map) = match (load(old), load(new)) {
+        (Ok(old_map), Ok(new_map)) => (old_map, new_map),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+    println!("{}", diff(&old_map, &new_map));
+    ExitCode::SUCCESS
+}
+
+/// Formats that hold a single level
+const LEVEL_FORMATS: &[&str] = &[
+    "tmj",
+    "tmx",
+    #[cfg(feature = "image")]
+    "png",
+];
+
+fn convert(map: &str, output: &str, level_name: Option<&str>) -> ExitCode {
+    let design_map = match load(map) {
+        Ok(design_map) => design_map,
+        Err(code) => return code,
+    };
+
+    let output_path = Path::new(output);
+    let extension = output_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let write_level = |path: &Path, level: &DesignLevel| -> Result<(), String> {
+        let contents = match extension {
+            "tmj" => level.to_tiled(TiledFormat::Tmj),
+            "tmx" => level.to_tiled(TiledFormat::Tmx),
+            #[cfg(feature = "image")]
+            "png" => {
+                // tileset paths are relative to the ldtk file
+                let atlas_dir = Path::new(map).parent().unwrap_or(Path::new("."));
+                level.render_png(atlas_dir.display(), path.display());
+                println!("Wrote {}", path.display());
+                return Ok(());
+            }
+            _ => unreachable!(),
+        };
+        fs::write(path, contents)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        println!("Wrote {}", path.display());
+        Ok(())
+    };
+
+    let result = match (extension, level_name) {
+        ("ldtk", _) => {
+            design_map.save_into(map, output);
+            println!("Wrote {}", output);
+            Ok(())
+        }
+        (extension, Some(level_name)) if LEVEL_FORMATS.contains(&extension) => {
+            match design_map.levels().get(level_name) {
+                Some(level) => write_level(output_path, level),
+                None => Err(format!(
+                    "{} does not have a level named {}",
+                    map, level_name
+                )),
+            }
+        }
+        (extension, None) if LEVEL_FORMATS.contains(&extension) => {
+            let stem = output_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default();
+            let mut levels: Vec<_> = design_map.levels().values().collect();
+            levels.sort_by(|a, b| a.name().cmp(b.name()));
+            levels.into_iter().try_for_each(|level| {
+                let file_name = format!("{}_{}.{}", stem, level.name(), extension);
+                write_level(&output_path.with_file_name(file_name), level)
+            })
+        }
+        _ => Err(format!(
+            "Can not convert to .{} files, supported formats: ldtk, {}",
+            extension,
+            LEVEL_FORMATS.join(", ")
+        )),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::design_map::{DesignLevel, TileContents};
+
+/// A rectangular part of a level borrowed from it by `DesignLevel::chunks`, nothing is copied
+#[derive(Clone, Copy, Debug)]
+pub struct Chunk<'a> {
+    level: &'a DesignLevel,
+    chunk_xy: (usize, usize),
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Chunk<'a> {
+    /// The coordinates of the chunk among the other chunks, (0, 0) being the top left one
+    pub fn chunk_xy(&self) -> (usize, usize) {
+        self.chunk_xy
+    }
+
+    /// The tile coordinates of the top left tile of the chunk in the level
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    /// The width of the chunk in tiles, chunks on the right edge of the level may be narrower
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the chunk in tiles, chunks on the bottom edge of the level may be shorter
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The tile at `(x, y)` relative to the chunk, `None` when outside of it
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a TileContents> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.level.get(self.origin.0 + x, self.origin.1 + y)
+    }
+
+    /// The rows of the chunk from top to bottom, each a slice of the level
+    pub fn rows(&self) -> impl Iterator<Item = &'a [TileContents]> {
+        let level = self.level;
+        let (x, y) = self.origin;
+        let width = self.width;
+        (y..y + self.height).map(move |row| {
+            let start = row * level.width + x;
+            &level.level[start..start + width]
+        })
+    }
+
+    /// Every tile of the chunk with its coordinates in the level, row by row from the top left
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((usize, usize), &'a TileContents)> {
+        let (x, y) = self.origin;
+        self.rows().enumerate().flat_map(move |(row, tiles)| {
+            tiles
+                .iter()
+                .enumerate()
+                .map(move |(column, tile)| ((x + column, y + row), tile))
+        })
+    }
+}
+
+impl DesignLevel {
+    /// Splits the level into chunks of `chunk_w` by `chunk_h` tiles, row by row from the top left.
+    ///
+    /// Panics when either size is 0.
+    pub fn chunks(&self, chunk_w: usize, chunk_h: usize) -> impl Iterator<Item = Chunk<'_>> {
+        assert!(
+            chunk_w > 0 && chunk_h > 0,
+            "Chunks need a size of at least 1 by 1"
+        );
+        let columns = self.width.div_ceil(chunk_w);
+        let rows = self.height.div_ceil(chunk_h);
+        (0..rows).flat_map(move |cy| {
+            (0..columns).filter_map(move |cx| self.chunk(chunk_w, chunk_h, (cx, cy)))
+        })
+    }
+
+    /// The chunk of `chunk_w` by `chunk_h` tiles at the chunk coordinates `chunk_xy`
+    ///
+    /// Panics when either size is 0.
+    pub fn chunk(
+        &self,
+        chunk_w: usize,
+        chunk_h: usize,
+        chunk_xy: (usize, usize),
+    ) -> Option<Chunk<'_>> {
+        assert!(
+            chunk_w > 0 && chunk_h > 0,
+            "Chunks need a size of at least 1 by 1"
+        );
+        let origin = (chunk_xy.0 * chunk_w, chunk_xy.1 * chunk_h);
+        if !self.in_bounds(origin.0, origin.1) {
+            return None;
+        }
+        Some(Chunk {
+            level: self,
+            chunk_xy,
+            origin,
+            width: chunk_w.min(self.width - origin.0),
+            height: chunk_h.min(self.height - origin.1),
+        })
+    }
+
+    /// The chunk of `chunk_w` by `chunk_h` tiles under the world pixel `(world_x, world_y)`,
+    /// `None` when the pixel is outside the level
+    ///
+    /// Panics when either size is 0.
+    pub fn chunk_at(
+        &self,
+        chunk_w: usize,
+        chunk_h: usize,
+        world_x: i64,
+        world_y: i64,
+    ) -> Option<Chunk<'_>> {
+        assert!(
+            chunk_w > 0 && chunk_h > 0,
+            "Chunks need a size of at least 1 by 1"
+        );
+        let (x, y) = self.world_px_to_tile(world_x, world_y)?;
+        self.chunk(chunk_w, chunk_h, (x / chunk_w, y / chunk_h))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        design_map::DesignLevel, project_builder::LevelBuilder, test_support::forest_project,
+    };
+
+    /// A 5x3 level of 8px tiles at (100, 0) in the world with values counting up from 1
+    fn level() -> DesignLevel {
+        let mut level = LevelBuilder::new("Level_0", (40, 24)).world_position(100, 0);
+        for y in 0..3 {
+            for x in 0..5 {
+                level = level.value("Values", (x, y), 1 + x + y * 5);
+            }
+        }
+        let names: Vec<String> = (1..=15).map(|n| n.to_string()).collect();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        let mut world = forest_project(8, &names).level(level).build();
+        world.levels.remove("Level_0").unwrap()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let level = level();
+        let chunks: Vec<_> = level.chunks(2, 2).collect();
+
+        assert_eq!(chunks.len(), 6);
+        assert_eq!(chunks[2].chunk_xy(), (2, 0));
+        assert_eq!((chunks[2].width(), chunks[2].height()), (1, 2));
+        assert_eq!((chunks[5].width(), chunks[5].height()), (1, 1));
+        assert_eq!(chunks[5].origin(), (4, 2));
+
+        let values: Vec<Vec<usize>> = chunks[1]
+            .rows()
+            .map(|row| row.iter().map(|tile| tile.value()).collect())
+            .collect();
+        assert_eq!(values, vec![vec![3, 4], vec![8, 9]]);
+        assert_eq!(chunks[1].get(1, 1).map(|tile| tile.value()), Some(9));
+        assert!(chunks[5].get(1, 0).is_none());
+
+        let tiles: usize = level.chunks(2, 2).map(|c| c.iter_coords().count()).sum();
+        assert_eq!(tiles, 15);
+        let (xy, tile) = chunks[4].iter_coords().last().unwrap();
+        assert_eq!(xy, (3, 2));
+        assert_eq!(tile.value(), 14);
+    }
+
+    #[test]
+    fn test_chunk_at() {
+        let level = level();
+
+        let chunk = level.chunk_at(2, 2, 100 + 8 * 3, 8 * 2).unwrap();
+        assert_eq!(chunk.chunk_xy(), (1, 1));
+        assert!(level.chunk_at(2, 2, 99, 0).is_none());
+        assert!(level.chunk(2, 2, (3, 0)).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_chunks() {
+        let _ = level().chunks(0, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_chunk() {
+        let _ = level().chunk(0, 2, (0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_chunk_at() {
+        let _ = level().chunk_at(2, 0, 100, 0);
+    }
+}
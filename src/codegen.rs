@@ -0,0 +1,367 @@
+//! Generates Rust enums and consts for the identifiers of a LDtk project, so typos in level,
+//! entity, layer, IntGrid value or enum names fail to compile instead of returning `None` at runtime.
+//!
+//! Meant to be called from the `main` of a build script:
+//! ```no_run
+//! println!("cargo:rerun-if-changed=maps/world.ldtk");
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! ldtk_map::codegen::write("maps/world.ldtk", format!("{}/ldtk_ids.rs", out_dir));
+//! ```
+//! and included where the identifiers should live:
+//! ```ignore
+//! mod ids {
+//!     include!(concat!(env!("OUT_DIR"), "/ldtk_ids.rs"));
+//! }
+//!
+//! let level = &map.levels()[ids::LevelId::Level0.name()];
+//! ```
+//!
+//! The generated code contains:
+//! - `LevelId`, `EntityId` and `LayerId` enums with a `name` as used by `DesignMap`
+//! - an enum per LDtk enum, readable from enum fields through `FromFieldValue`
+//! - a module per IntGrid layer under `int_grid` with a const per named value
+
+use std::{collections::HashSet, fmt::Write, fs};
+
+use crate::{design_map::int_grid_values, enums::enum_def_list, ldtk_helpers::get_raw_world};
+
+/// Rust keywords, including the ones reserved for later editions, that can not be used as
+/// identifiers. `self`, `Self`, `super` and `crate` can not even be raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// The types generated for every project, which LDtk enums must not be named like
+const GENERATED_TYPES: [&str; 3] = ["LevelId", "EntityId", "LayerId"];
+
+/// Generates the Rust source for the identifiers of the ldtk file located at path
+pub fn generate(path: impl ToString) -> String {
+    let path = path.to_string();
+    let ldtk_world = get_raw_world(path.clone());
+    let defs = &ldtk_world.defs;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by ldtk_map::codegen from {}, do not edit.\n",
+        path
+    );
+
+    let levels: Vec<String> = ldtk_world
+        .levels
+        .iter()
+        .map(|level| level.identifier.clone())
+        .collect();
+    write_enum(
+        &mut out,
+        "The levels of the project, `name` being the key of `DesignMap::levels`",
+        "LevelId",
+        &named_variants(&levels, |name| name.to_string()),
+    );
+
+    let entities: Vec<String> = defs
+        .entities
+        .iter()
+        .flatten()
+        .filter_map(|entity| entity.identifier.clone())
+        .collect();
+    write_enum(
+        &mut out,
+        "The entities of the project, `name` being the one of `TileContents::entity_name`",
+        "EntityId",
+        &named_variants(&entities, |name| name.replace('_', " ")),
+    );
+
+    let layers: Vec<String> = defs
+        .layers
+        .iter()
+        .flatten()
+        .filter_map(|layer| layer.identifier.clone())
+        .collect();
+    write_enum(
+        &mut out,
+        "The layers of the project",
+        "LayerId",
+        &named_variants(&layers, |name| name.to_string()),
+    );
+
+    let mut types: HashSet<String> = GENERATED_TYPES.iter().map(|t| t.to_string()).collect();
+    for def in enum_def_list(defs) {
+        let values: Vec<String> = def
+            .values()
+            .iter()
+            .map(|value| value.id().to_string())
+            .collect();
+        let name = unique(&mut types, pascal_case(def.identifier()));
+        write_enum(
+            &mut out,
+            &format!("The values of the LDtk enum `{}`", def.identifier()),
+            &name,
+            &named_variants(&values, |value| value.to_string()),
+        );
+        let _ = writeln!(
+            out,
+            "impl ::ldtk_map::FromFieldValue for {name} {{
+    fn from_field(value: &::ldtk_map::prelude::FieldValue) -> ::std::option::Option<Self> {{
+        let value = value.as_enum()?;
+        if value.enum_identifier() != {identifier:?} {{
+            return ::std::option::Option::None;
+        }}
+        value.parse().ok()
+    }}
+}}
+",
+            identifier = def.identifier()
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "/// The named values of the IntGrid layers of the project"
+    );
+    let _ = writeln!(out, "#[allow(dead_code)]");
+    let _ = writeln!(out, "pub mod int_grid {{");
+    let mut modules = HashSet::new();
+    for layer in defs.layers.iter().flatten() {
+        // layers without any values, Entities and Tiles layers too, get no module
+        if layer.int_grid_values.iter().flatten().next().is_none() {
+            continue;
+        }
+        let Some(identifier) = layer.identifier.as_ref() else {
+            continue;
+        };
+        let module = unique(&mut modules, module_name(identifier));
+        let _ = writeln!(out, "    /// The values of the `{}` layer", identifier);
+        let _ = writeln!(out, "    pub mod {} {{", module);
+        let mut consts = HashSet::new();
+        for value in int_grid_values(defs, layer.uid) {
+            let Some(value_identifier) = value.identifier() else {
+                continue;
+            };
+            let name = unique(&mut consts, const_name(value_identifier));
+            let _ = writeln!(out, "        /// `{}`", value_identifier);
+            let _ = writeln!(
+                out,
+                "        pub const {}: usize = {};",
+                name,
+                value.value()
+            );
+        }
+        let _ = writeln!(out, "    }}");
+    }
+    let _ = writeln!(out, "}}");
+
+    out
+}
+
+/// Generates the Rust source for the ldtk file located at path into `out_path`,
+/// leaving the file untouched when nothing changed so dependent code is not rebuilt
+pub fn write(path: impl ToString, out_path: impl ToString) {
+    let out_path = out_path.to_string();
+    let source = generate(path);
+    if fs::read_to_string(&out_path).is_ok_and(|current| current == source) {
+        return;
+    }
+    if let Err(e) = fs::write(&out_path, source) {
+        panic!(
+            "Should have been able to write the file: {} | Error: {}",
+            out_path, e
+        )
+    }
+}
+
+/// Pairs the variant of every identifier with the name `name` gives it
+fn named_variants(identifiers: &[String], name: impl Fn(&str) -> String) -> Vec<(String, String)> {
+    let mut taken = HashSet::new();
+    identifiers
+        .iter()
+        .map(|identifier| {
+            (
+                unique(&mut taken, pascal_case(identifier)),
+                name(identifier),
+            )
+        })
+        .collect()
+}
+
+fn write_enum(out: &mut String, doc: &str, name: &str, variants: &[(String, String)]) {
+    let _ = writeln!(out, "/// {}", doc);
+    // the generated items are only partly used by most games, and their variants are
+    // named after the LDtk project rather than the enum
+    let _ = writeln!(out, "#[allow(dead_code, clippy::enum_variant_names)]");
+    let _ = writeln!(out, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]");
+    let _ = writeln!(out, "pub enum {} {{", name);
+    for (variant, value) in variants {
+        let _ = writeln!(out, "    /// `{}`", value);
+        let _ = writeln!(out, "    {},", variant);
+    }
+    let _ = writeln!(out, "}}\n");
+
+    let all: Vec<String> = variants
+        .iter()
+        .map(|(variant, _)| format!("{}::{}", name, variant))
+        .collect();
+    let _ = writeln!(out, "#[allow(dead_code)]");
+    let _ = writeln!(out, "impl {} {{", name);
+    let _ = writeln!(out, "    /// Every value in the order of the LDtk project");
+    let _ = writeln!(
+        out,
+        "    pub const ALL: &'static [{}] = &[{}];\n",
+        name,
+        all.join(", ")
+    );
+    let _ = writeln!(out, "    /// The name of the value as used by ldtk_map");
+    let _ = writeln!(out, "    pub const fn name(self) -> &'static str {{");
+    let _ = writeln!(out, "        match self {{");
+    for (variant, value) in variants {
+        let _ = writeln!(out, "            {}::{} => {:?},", name, variant, value);
+    }
+    let _ = writeln!(out, "        }}\n    }}\n}}\n");
+
+    let _ = writeln!(out, "impl ::std::str::FromStr for {} {{", name);
+    let _ = writeln!(out, "    type Err = ::std::string::String;\n");
+    let _ = writeln!(
+        out,
+        "    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {{"
+    );
+    let _ = writeln!(out, "        match s {{");
+    for (variant, value) in variants {
+        let _ = writeln!(
+            out,
+            "            {:?} => ::std::result::Result::Ok({}::{}),",
+            value, name, variant
+        );
+    }
+    let _ = writeln!(
+        out,
+        "            _ => ::std::result::Result::Err(::std::format!(\"{{}} is not a {}\", s)),",
+        name
+    );
+    let _ = writeln!(out, "        }}\n    }}\n}}\n");
+
+    let _ = writeln!(out, "impl ::std::fmt::Display for {} {{", name);
+    let _ = writeln!(
+        out,
+        "    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {{"
+    );
+    let _ = writeln!(out, "        f.write_str(self.name())\n    }}\n}}\n");
+}
+
+/// Turns `Level_0` into `Level0` and `deep water` into `DeepWater`, avoiding keywords
+fn pascal_case(identifier: &str) -> String {
+    let name: String = words(identifier)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    not_keyword(leading_letter(name, "V"))
+}
+
+/// Turns `Deep water` into `DEEP_WATER`
+fn const_name(identifier: &str) -> String {
+    leading_letter(words(identifier).join("_").to_uppercase(), "V_")
+}
+
+/// Turns `Values` into `values`, avoiding keywords
+fn module_name(identifier: &str) -> String {
+    not_keyword(leading_letter(
+        words(identifier).join("_").to_lowercase(),
+        "l_",
+    ))
+}
+
+/// Appends `_` to keywords
+fn not_keyword(name: String) -> String {
+    match KEYWORDS.contains(&name.as_str()) {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+/// Splits an identifier on anything that can not be in a Rust identifier
+fn words(identifier: &str) -> Vec<&str> {
+    identifier
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Identifiers must not start with a digit, nor be empty
+fn leading_letter(name: String, prefix: &str) -> String {
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => name,
+        _ => format!("{}{}", prefix, name),
+    }
+}
+
+/// Appends a number to `name` until it is not taken yet
+fn unique(taken: &mut HashSet<String>, name: String) -> String {
+    let mut unique_name = name.clone();
+    let mut count = 2;
+    while !taken.insert(unique_name.clone()) {
+        unique_name = format!("{}{}", name, count);
+        count += 1;
+    }
+    unique_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{const_name, generate, module_name, pascal_case};
+    use crate::{
+        project_builder::LevelBuilder,
+        test_support::{forest_project, TempPath},
+    };
+
+    #[test]
+    fn test_names() {
+        assert_eq!(pascal_case("Level_0"), "Level0");
+        assert_eq!(pascal_case("deep water"), "DeepWater");
+        assert_eq!(pascal_case("2nd_floor"), "V2ndFloor");
+        assert_eq!(const_name("Deep water"), "DEEP_WATER");
+        assert_eq!(module_name("Values"), "values");
+        assert_eq!(module_name("Type"), "type_");
+        assert_eq!(module_name("gen"), "gen_");
+        assert_eq!(pascal_case("self"), "Self_");
+        assert_eq!(pascal_case("try"), "Try");
+    }
+
+    #[test]
+    fn test_generate() {
+        let path = TempPath::new("codegen.ldtk");
+        forest_project(8, &["wall", "deep water"])
+            .entity("Gold_Chest", (8, 8), &[])
+            .level(LevelBuilder::new("Level_0", (16, 16)))
+            .level(LevelBuilder::new("Level_1", (16, 16)))
+            .save(&path);
+
+        let source = generate(&path);
+        assert!(source.contains("pub enum LevelId {"), "{}", source);
+        assert!(source.contains("    Level0,\n"), "{}", source);
+        assert!(
+            source.contains("LevelId::Level1 => \"Level_1\","),
+            "{}",
+            source
+        );
+        assert!(
+            source.contains("EntityId::GoldChest => \"Gold Chest\","),
+            "{}",
+            source
+        );
+        assert!(source.contains("pub const ALL: &'static [LayerId] = &[LayerId::Entities, LayerId::Values, LayerId::Ground];"), "{}", source);
+        assert!(source.contains("    pub mod values {"), "{}", source);
+        assert!(
+            source.contains("        pub const DEEP_WATER: usize = 2;"),
+            "{}",
+            source
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::design_map::{DesignLevel, TileContents};
+
+/// An axis-aligned rectangle of solid tiles made by `DesignLevel::collision_rects`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionRect {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    grid_size_px: usize,
+}
+
+impl CollisionRect {
+    /// The x coordinate of the top left tile
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    /// The y coordinate of the top left tile
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    /// The width in tiles
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height in tiles
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The top left corner in pixels, relative to the level
+    pub fn xy_px(&self) -> (usize, usize) {
+        (self.x * self.grid_size_px, self.y * self.grid_size_px)
+    }
+
+    /// The width and height in pixels
+    pub fn size_px(&self) -> (usize, usize) {
+        (
+            self.width * self.grid_size_px,
+            self.height * self.grid_size_px,
+        )
+    }
+}
+
+impl DesignLevel {
+    /// Merges the tiles matching `solid` into rectangles, e.g. `|tile| tile.value() == wall`,
+    /// so a physics engine gets a few large colliders instead of one per tile.
+    ///
+    /// Rectangles are grown greedily, first as wide as possible and then as tall as the
+    /// whole width allows, scanning from the top left. They never overlap.
+    pub fn collision_rects(&self, solid: impl Fn(&TileContents) -> bool) -> Vec<CollisionRect> {
+        let mut remaining: Vec<bool> = self.level.iter().map(solid).collect();
+        let mut rects = vec![];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !remaining[y * self.width + x] {
+                    continue;
+                }
+
+                let width = (x..self.width)
+                    .take_while(|x| remaining[y * self.width + x])
+                    .count();
+                let height = (y..self.height)
+                    .take_while(|y| {
+                        let row = y * self.width + x;
+                        remaining[row..row + width].iter().all(|solid| *solid)
+                    })
+                    .count();
+                for row in y..y + height {
+                    let start = row * self.width + x;
+                    remaining[start..start + width].fill(false);
+                }
+
+                rects.push(CollisionRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    grid_size_px: self.grid_size_px,
+                });
+            }
+        }
+
+        rects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollisionRect;
+    use crate::{project_builder::LevelBuilder, test_support::forest_project};
+
+    #[test]
+    fn test_collision_rects() {
+        // ##..
+        // ##.#
+        // ...#
+        let mut level = LevelBuilder::new("Level_0", (64, 48));
+        for xy in [(0, 0), (1, 0), (0, 1), (1, 1), (3, 1), (3, 2)] {
+            level = level.value("Values", xy, 1);
+        }
+        let world = forest_project(16, &["wall"]).level(level).build();
+        let level = &world.levels()["Level_0"];
+        let wall = level.int_grid_value("wall").unwrap();
+
+        let rects = level.collision_rects(|tile| tile.value() == wall);
+        assert_eq!(
+            rects,
+            vec![
+                CollisionRect {
+                    x: 0,
+                    y: 0,
+                    width: 2,
+                    height: 2,
+                    grid_size_px: 16
+                },
+                CollisionRect {
+                    x: 3,
+                    y: 1,
+                    width: 1,
+                    height: 2,
+                    grid_size_px: 16
+                },
+            ]
+        );
+        assert_eq!(rects[1].xy_px(), (48, 16));
+        assert_eq!(rects[1].size_px(), (16, 32));
+        assert!(level.collision_rects(|_| false).is_empty());
+        assert_eq!(level.collision_rects(|_| true).len(), 1);
+    }
+}
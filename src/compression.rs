@@ -0,0 +1,128 @@
+//! Compressed ldtk files are recognized by their first bytes instead of their extension,
+//! so a `.ldtk.gz`, a baked map or a gzipped `.ldtkl` all load the same way.
+
+/// Every gzip stream starts with these bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Every zstd frame starts with these bytes
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Turns the contents of a ldtk file into text, decompressing them when they are
+/// gzip or zstd compressed. `path` is only used in errors.
+pub(crate) fn decompress(bytes: &[u8], path: &str) -> Result<String, String> {
+    let text = if bytes.starts_with(&GZIP_MAGIC) {
+        gunzip(bytes, path)?
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        unzstd(bytes, path)?
+    } else {
+        bytes.to_vec()
+    };
+    String::from_utf8(text).map_err(|e| format!("Could not load level at {} | Error: {}", path, e))
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8], path: &str) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut text = vec![];
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut text)
+        .map_err(|e| format!("Could not decompress {} | Error: {}", path, e))?;
+    Ok(text)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_bytes: &[u8], path: &str) -> Result<Vec<u8>, String> {
+    Err(format!(
+        "{} is gzip compressed, enable the gzip feature of ldtk_map to load it",
+        path
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(mut bytes: &[u8], path: &str) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let error = |e: &dyn std::fmt::Display| format!("Could not decompress {} | Error: {}", path, e);
+    let mut text = vec![];
+    ruzstd::decoding::StreamingDecoder::new(&mut bytes)
+        .map_err(|e| error(&e))?
+        .read_to_end(&mut text)
+        .map_err(|e| error(&e))?;
+    Ok(text)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_bytes: &[u8], path: &str) -> Result<Vec<u8>, String> {
+    Err(format!(
+        "{} is zstd compressed, enable the zstd feature of ldtk_map to load it",
+        path
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress;
+
+    const PROJECT: &str = "./tests/testmaps/two_tileatlases.ldtk";
+
+    #[test]
+    fn test_plain_text() {
+        let contents = std::fs::read_to_string(PROJECT).unwrap();
+        assert_eq!(decompress(contents.as_bytes(), PROJECT), Ok(contents));
+        assert!(decompress(&[0xff, 0xfe], PROJECT)
+            .unwrap_err()
+            .starts_with("Could not load level at"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Write;
+
+        use crate::{design_map::DesignMap, test_support::TempPath};
+
+        let contents = std::fs::read_to_string(PROJECT).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(decompress(&compressed, PROJECT), Ok(contents.clone()));
+
+        let path = TempPath::new("gzip.ldtk.gz");
+        std::fs::write(&path, compressed).unwrap();
+        let map = DesignMap::load(&path);
+        assert_eq!(map.levels().len(), DesignMap::load(PROJECT).levels().len());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_needs_feature() {
+        assert_eq!(
+            decompress(&[0x1f, 0x8b, 0x08], "world.ldtk.gz"),
+            Err(
+                "world.ldtk.gz is gzip compressed, enable the gzip feature of ldtk_map to load it"
+                    .to_string()
+            )
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+        let contents = std::fs::read_to_string(PROJECT).unwrap();
+        let compressed = compress_to_vec(contents.as_bytes(), CompressionLevel::Fastest);
+        assert_eq!(decompress(&compressed, PROJECT), Ok(contents));
+        assert!(decompress(&compressed[..compressed.len() / 2], PROJECT)
+            .unwrap_err()
+            .starts_with("Could not decompress"));
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn test_zstd_needs_feature() {
+        assert!(decompress(&[0x28, 0xb5, 0x2f, 0xfd], "world.ldtk.zst")
+            .unwrap_err()
+            .contains("enable the zstd feature"));
+    }
+}
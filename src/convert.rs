@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use crate::{
+    design_map::DesignLevel,
+    enums::EnumValue,
+    fields::{EntityRef, FieldValue, TileRect},
+    registry::EntitySpawn,
+};
+
+/// Builds a struct out of an entity placed in LDtk, usually through `#[derive(LdtkEntity)]`
+/// of the `derive` feature.
+///
+/// ```ignore
+/// #[derive(LdtkEntity)]
+/// struct Monster {
+///     #[ldtk(grid_xy)]
+///     xy: (usize, usize),
+///     #[ldtk(field = "hp")]
+///     health: i64,
+///     loot: Vec<String>,
+/// }
+/// ```
+///
+/// Fields are read from the LDtk field of the same name unless `#[ldtk(field = "...")]` says
+/// otherwise, and their type must implement `FromFieldValue`. The attributes `name`, `tag`, `iid`,
+/// `grid_xy` and `world_px` read those parts of the `EntitySpawn` instead.
+pub trait LdtkEntity: Sized {
+    /// Reads the entity, failing on missing fields or fields of another type
+    fn from_spawn(spawn: &EntitySpawn) -> Result<Self, String>;
+}
+
+/// Builds a struct out of the fields of a level, usually through `#[derive(LdtkLevelFields)]`
+/// of the `derive` feature.
+///
+/// Takes the same attributes as `LdtkEntity`, `name`, `iid` and `world_px` reading the level.
+pub trait LdtkLevelFields: Sized {
+    /// Reads the fields of the level, failing on missing fields or fields of another type
+    fn from_level(level: &DesignLevel) -> Result<Self, String>;
+}
+
+/// A Rust type an LDtk field can be read as.
+///
+/// Implement it for your own types, e.g. a Rust enum matching an LDtk enum:
+/// ```ignore
+/// impl FromFieldValue for ItemKind {
+///     fn from_field(value: &FieldValue) -> Option<Self> {
+///         value.as_enum()?.parse().ok()
+///     }
+/// }
+/// ```
+pub trait FromFieldValue: Sized {
+    /// Converts the value, `None` if it is of another type
+    fn from_field(value: &FieldValue) -> Option<Self>;
+
+    /// The value used when the field does not exist at all, `None` making it an error
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Reads the field `identifier` of `owner`, used by the derive macros
+#[doc(hidden)]
+pub fn __field<T: FromFieldValue>(
+    fields: &HashMap<String, FieldValue>,
+    identifier: &str,
+    owner: &str,
+) -> Result<T, String> {
+    match fields.get(identifier) {
+        Some(value) => T::from_field(value).ok_or_else(|| {
+            format!(
+                "field {} of {} is not a {} but {:?}",
+                identifier,
+                owner,
+                std::any::type_name::<T>(),
+                value
+            )
+        }),
+        None => T::from_missing().ok_or_else(|| format!("{} has no field {}", owner, identifier)),
+    }
+}
+
+macro_rules! from_field_value {
+    ($($ty:ty => $convert:expr),* $(,)?) => {
+        $(
+            impl FromFieldValue for $ty {
+                fn from_field(value: &FieldValue) -> Option<Self> {
+                    let convert: fn(&FieldValue) -> Option<Self> = $convert;
+                    convert(value)
+                }
+            }
+        )*
+    };
+}
+
+from_field_value! {
+    i64 => |value| value.as_int(),
+    i32 => |value| value.as_int()?.try_into().ok(),
+    u32 => |value| value.as_int()?.try_into().ok(),
+    usize => |value| value.as_int()?.try_into().ok(),
+    f64 => |value| value.as_float(),
+    f32 => |value| value.as_float().map(|n| n as f32),
+    bool => |value| value.as_bool(),
+    String => |value| match value {
+        FieldValue::Enum(value) => Some(value.value().to_string()),
+        value => Some(value.as_str()?.to_string()),
+    },
+    (i64, i64) => |value| value.as_point(),
+    EnumValue => |value| value.as_enum().cloned(),
+    EntityRef => |value| match value {
+        FieldValue::EntityRef(entity_ref) => Some(entity_ref.clone()),
+        _ => None,
+    },
+    TileRect => |value| match value {
+        FieldValue::Tile(tile) => Some(*tile),
+        _ => None,
+    },
+    FieldValue => |value| Some(value.clone()),
+}
+
+impl<T: FromFieldValue> FromFieldValue for Option<T> {
+    fn from_field(value: &FieldValue) -> Option<Self> {
+        match value {
+            FieldValue::Null => Some(None),
+            value => T::from_field(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromFieldValue> FromFieldValue for Vec<T> {
+    fn from_field(value: &FieldValue) -> Option<Self> {
+        value.as_array()?.iter().map(T::from_field).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::__field;
+    use crate::fields::FieldValue;
+
+    #[test]
+    fn test_read_fields() {
+        let fields = HashMap::from([
+            ("hp".to_string(), FieldValue::Int(25)),
+            ("speed".to_string(), FieldValue::Null),
+            (
+                "loot".to_string(),
+                FieldValue::Array(vec![FieldValue::String("Sword".to_string())]),
+            ),
+        ]);
+
+        assert_eq!(__field::<i64>(&fields, "hp", "Monster"), Ok(25));
+        assert_eq!(__field::<f32>(&fields, "hp", "Monster"), Ok(25.0));
+        assert_eq!(
+            __field::<Option<f64>>(&fields, "speed", "Monster"),
+            Ok(None)
+        );
+        assert_eq!(
+            __field::<Option<f64>>(&fields, "armor", "Monster"),
+            Ok(None)
+        );
+        assert_eq!(
+            __field::<Vec<String>>(&fields, "loot", "Monster"),
+            Ok(vec!["Sword".to_string()])
+        );
+        assert_eq!(
+            __field::<i64>(&fields, "armor", "Monster"),
+            Err("Monster has no field armor".to_string())
+        );
+        assert!(__field::<bool>(&fields, "hp", "Monster")
+            .unwrap_err()
+            .starts_with("field hp of Monster is not a bool"));
+    }
+}
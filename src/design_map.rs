@@ -1,31 +1,61 @@
 use std::collections::HashMap;
 
 use crate::{
-    ldtk_helpers::{get_raw_world, gridpx_to_idx, src_to_atlas_index},
-    ldtk_structs::{LDtk, Level},
+    enums::{enum_defs, EnumDef, EnumValue, EnumValueDef},
+    fields::{field_instances, FieldValue},
+    ldtk_helpers::{atlas_index_to_src, get_raw_world, gridpx_to_idx, src_to_atlas_index},
+    ldtk_structs::{Defs, GridTile, LDtk, LayerInstance, Level},
+    toc::{toc_entries, TocEntry},
 };
 
+/// Offsets to the tiles sharing an edge, clockwise from up
+pub(crate) const NEIGHBOURS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+/// Offsets to the tiles sharing an edge or a corner, clockwise from up
+pub(crate) const NEIGHBOURS8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
 /// The friendly, opiniated game map file. Contains the raw data
 /// of the map made in ldtk but formatted in a way to be extremely simple
 /// when used in game
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "bevy", derive(bevy::asset::Asset, bevy::reflect::TypePath))]
 pub struct DesignMap {
-    levels: HashMap<String, DesignLevel>,
-    tilesets: HashMap<usize, String>,
+    pub(crate) levels: HashMap<String, DesignLevel>,
+    pub(crate) tilesets: HashMap<usize, DesignTileset>,
+    pub(crate) toc: Vec<TocEntry>,
+    pub(crate) enums: HashMap<String, EnumDef>,
 }
 
 /// Represents a single level designed in LDtk, contains the minimal
 /// amount of data necessary to rebuild levels in game.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DesignLevel {
-    level: Vec<TileContents>,
-    level_name: String,
-    width: usize,
-    height: usize,
-    grid_size_px: usize,
-    tileset_name: String,
-    world_x: i64,
-    world_y: i64,
+    pub(crate) level: Vec<TileContents>,
+    pub(crate) level_name: String,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) grid_size_px: usize,
+    pub(crate) tileset: DesignTileset,
+    pub(crate) int_grid_values: Vec<IntGridValue>,
+    pub(crate) world_x: i64,
+    pub(crate) world_y: i64,
+    pub(crate) world_px: (i64, i64),
+    pub(crate) world_depth: i64,
+    pub(crate) size_px: (usize, usize),
+    /// Position of the level in the ldtk file, later levels are drawn over earlier ones
+    pub(crate) order: usize,
+    pub(crate) iid: Option<String>,
+    pub(crate) fields: HashMap<String, FieldValue>,
+    pub(crate) tile_layers: Vec<TileLayer>,
+    pub(crate) ground_depth: usize,
 }
 
 impl DesignLevel {
@@ -36,18 +66,27 @@ impl DesignLevel {
             width: 0,
             height: 0,
             grid_size_px: 0,
-            tileset_name: "Unset".to_string(),
+            tileset: DesignTileset::empty(),
+            int_grid_values: vec![],
             world_x: 0,
             world_y: 0,
+            world_px: (0, 0),
+            world_depth: 0,
+            size_px: (0, 0),
+            order: 0,
+            iid: None,
+            fields: HashMap::new(),
+            tile_layers: vec![],
+            ground_depth: 0,
         }
     }
 
-    /// The x coordinate of the map in the world
+    /// The x coordinate of the map in the world, in tiles of the grid size of the level
     pub fn world_tile_x(&self) -> i64 {
         self.world_x
     }
 
-    /// The y coordinate of the map in the world
+    /// The y coordinate of the map in the world, in tiles of the grid size of the level
     pub fn world_tile_y(&self) -> i64 {
         self.world_y
     }
@@ -57,16 +96,122 @@ impl DesignLevel {
         (self.world_x, self.world_y)
     }
 
+    /// The x and y coordinates of the top left corner of the level in the world, in pixels
+    pub fn world_px(&self) -> (i64, i64) {
+        self.world_px
+    }
+
+    /// The depth of the level in the world, levels with a higher depth are drawn over the others
+    pub fn world_depth(&self) -> i64 {
+        self.world_depth
+    }
+
+    /// The width of the level in pixels
+    pub fn width_px(&self) -> usize {
+        self.size_px.0
+    }
+
+    /// The height of the level in pixels
+    pub fn height_px(&self) -> usize {
+        self.size_px.1
+    }
+
     /// The contents of the level
     pub fn level(&self) -> &[TileContents] {
         self.level.as_ref()
     }
 
+    /// The mutable contents of the level, changes can be written back with `DesignMap::save_into`
+    pub fn level_mut(&mut self) -> &mut [TileContents] {
+        self.level.as_mut()
+    }
+
+    /// Whether `(x, y)` is a tile of the level
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The tile at `(x, y)`, `None` when the coordinates are outside the level
+    pub fn get(&self, x: usize, y: usize) -> Option<&TileContents> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.level.get(gridpx_to_idx((x, y), self.width))
+    }
+
+    /// The mutable tile at `(x, y)`, `None` when the coordinates are outside the level
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut TileContents> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.level.get_mut(gridpx_to_idx((x, y), self.width))
+    }
+
+    /// Every tile of the level with its coordinates, row by row from the top left
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((usize, usize), &TileContents)> {
+        let width = self.width.max(1);
+        self.level
+            .iter()
+            .enumerate()
+            .map(move |(idx, tile)| ((idx % width, idx / width), tile))
+    }
+
+    /// The rows of the level from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[TileContents]> {
+        self.level.chunks(self.width.max(1))
+    }
+
+    /// The tiles up, right, down and left of `(x, y)` that are inside the level
+    pub fn neighbours4(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &TileContents)> {
+        self.neighbours(x, y, &NEIGHBOURS4)
+    }
+
+    /// The tiles around `(x, y)` including diagonals that are inside the level
+    pub fn neighbours8(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &TileContents)> {
+        self.neighbours(x, y, &NEIGHBOURS8)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &'a TileContents)> {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(*dx)?;
+            let ny = y.checked_add_signed(*dy)?;
+            Some(((nx, ny), self.get(nx, ny)?))
+        })
+    }
+
     /// The name of the level
     pub fn name(&self) -> &str {
         self.level_name.as_ref()
     }
 
+    /// The unique id LDtk gave the level, it stays the same when the level is renamed
+    pub fn iid(&self) -> Option<&str> {
+        self.iid.as_deref()
+    }
+
+    /// The fields set on the level in LDtk by their identifier
+    pub fn fields(&self) -> &HashMap<String, FieldValue> {
+        &self.fields
+    }
+
+    /// The field named `identifier` of the level
+    pub fn field(&self, identifier: &str) -> Option<&FieldValue> {
+        self.fields.get(identifier)
+    }
+
     /// The width of the level based on the Ground layer
     pub fn width(&self) -> usize {
         self.width
@@ -84,25 +229,294 @@ impl DesignLevel {
 
     /// The tileset identifier being used for this level
     pub fn tileset_name(&self) -> &str {
-        self.tileset_name.as_ref()
+        self.tileset.identifier.as_ref()
+    }
+
+    /// The tileset being used for this level
+    pub fn tileset(&self) -> &DesignTileset {
+        &self.tileset
+    }
+
+    /// The values defined for the Values layer used by this level
+    pub fn int_grid_values(&self) -> &[IntGridValue] {
+        self.int_grid_values.as_ref()
+    }
+
+    /// The value of the IntGrid value named `identifier` in LDtk, e.g. "wall"
+    pub fn int_grid_value(&self, identifier: &str) -> Option<usize> {
+        self.int_grid_values
+            .iter()
+            .find(|value| value.identifier.as_deref() == Some(identifier))
+            .map(|value| value.value)
+    }
+
+    /// The tile layers besides Ground from the bottom up, with the sprites LDtk placed on them
+    pub fn tile_layers(&self) -> &[TileLayer] {
+        &self.tile_layers
+    }
+
+    /// How many of the `tile_layers` are drawn below the Ground layer
+    pub fn ground_depth(&self) -> usize {
+        self.ground_depth
+    }
+}
+
+/// A value that can be painted on the Values layer, as defined in LDtk
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntGridValue {
+    pub(crate) value: usize,
+    pub(crate) identifier: Option<String>,
+    pub(crate) color: String,
+}
+
+impl IntGridValue {
+    /// The value stored in `TileContents::value` for tiles painted with it
+    pub fn value(&self) -> usize {
+        self.value
+    }
+
+    /// The name given to the value in LDtk
+    pub fn identifier(&self) -> Option<&str> {
+        self.identifier.as_deref()
+    }
+
+    /// The color of the value in LDtk as a hex string, e.g. `#BE4A2F`
+    pub fn color(&self) -> &str {
+        self.color.as_ref()
+    }
+}
+
+/// Represents a tileset defined in LDtk, the atlas the sprites of a level come from
+#[derive(Clone, Debug)]
+pub struct DesignTileset {
+    pub(crate) identifier: String,
+    pub(crate) uid: usize,
+    pub(crate) rel_path: Option<String>,
+    pub(crate) embed_atlas: Option<String>,
+    pub(crate) width_px: usize,
+    pub(crate) height_px: usize,
+    pub(crate) tile_grid_size: usize,
+    pub(crate) spacing: usize,
+    pub(crate) padding: usize,
+}
+
+impl DesignTileset {
+    fn empty() -> Self {
+        Self {
+            identifier: "Unset".to_string(),
+            uid: 0,
+            rel_path: None,
+            embed_atlas: None,
+            width_px: 0,
+            height_px: 0,
+            tile_grid_size: 0,
+            spacing: 0,
+            padding: 0,
+        }
+    }
+
+    /// The identifier of the tileset
+    pub fn name(&self) -> &str {
+        self.identifier.as_ref()
+    }
+
+    /// The unique id LDtk gave the tileset
+    pub fn uid(&self) -> usize {
+        self.uid
+    }
+
+    /// The path to the atlas image, relative to the ldtk file
+    pub fn rel_path(&self) -> Option<&str> {
+        self.rel_path.as_deref()
+    }
+
+    /// The name of the atlas built into LDtk if the tileset uses one instead of an image file
+    pub fn embed_atlas(&self) -> Option<&str> {
+        self.embed_atlas.as_deref()
+    }
+
+    /// The width of the atlas image in px
+    pub fn width_px(&self) -> usize {
+        self.width_px
+    }
+
+    /// The height of the atlas image in px
+    pub fn height_px(&self) -> usize {
+        self.height_px
+    }
+
+    /// The size of a single sprite in the atlas in px
+    pub fn tile_grid_size(&self) -> usize {
+        self.tile_grid_size
+    }
+
+    /// The space between sprites in the atlas in px
+    pub fn spacing(&self) -> usize {
+        self.spacing
+    }
+
+    /// The space around the edge of the atlas in px
+    pub fn padding(&self) -> usize {
+        self.padding
+    }
+
+    /// The amount of sprites in a row of the atlas
+    pub fn columns(&self) -> usize {
+        let step = self.tile_grid_size + self.spacing;
+        if step == 0 {
+            return 0;
+        }
+        (self.width_px.saturating_sub(self.padding * 2) + self.spacing) / step
+    }
+
+    /// The amount of sprites in a column of the atlas
+    pub fn rows(&self) -> usize {
+        let step = self.tile_grid_size + self.spacing;
+        if step == 0 {
+            return 0;
+        }
+        (self.height_px.saturating_sub(self.padding * 2) + self.spacing) / step
+    }
+
+    /// Converts the src (x, y) of a sprite `grid_size` px large into its atlas index
+    pub(crate) fn src_to_atlas_index(&self, (x, y): (usize, usize), grid_size: usize) -> usize {
+        src_to_atlas_index(
+            (
+                x.saturating_sub(self.padding),
+                y.saturating_sub(self.padding),
+            ),
+            grid_size + self.spacing,
+        )
+    }
+
+    /// Converts the atlas index of a sprite `grid_size` px large back into its src (x, y)
+    pub(crate) fn atlas_index_to_src(
+        &self,
+        atlas_index: usize,
+        grid_size: usize,
+    ) -> (usize, usize) {
+        let (x, y) = atlas_index_to_src(atlas_index, grid_size + self.spacing);
+        (x + self.padding, y + self.padding)
+    }
+}
+
+/// The sprites of a layer other than Ground, placed by hand in a Tiles layer or by the rules
+/// of an AutoLayer or IntGrid layer
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+    pub(crate) identifier: String,
+    pub(crate) tileset: DesignTileset,
+    pub(crate) grid_size_px: usize,
+    pub(crate) offset_px: (i64, i64),
+    pub(crate) opacity: f64,
+    pub(crate) tiles: Vec<LayerTile>,
+}
+
+impl TileLayer {
+    /// The name of the layer in LDtk
+    pub fn name(&self) -> &str {
+        self.identifier.as_ref()
+    }
+
+    /// The tileset the sprites come from
+    pub fn tileset(&self) -> &DesignTileset {
+        &self.tileset
+    }
+
+    /// The size in pixels of a tile of the layer
+    pub fn grid_size_px(&self) -> usize {
+        self.grid_size_px
+    }
+
+    /// How far in pixels the layer is moved from the top left corner of the level
+    pub fn offset_px(&self) -> (i64, i64) {
+        self.offset_px
+    }
+
+    /// The opacity of the whole layer, from 0 to 1
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// The sprites in the order LDtk draws them, later ones over earlier ones
+    pub fn tiles(&self) -> &[LayerTile] {
+        &self.tiles
+    }
+}
+
+/// A sprite of a `TileLayer`
+#[derive(Clone, Debug)]
+pub struct LayerTile {
+    pub(crate) px: (usize, usize),
+    pub(crate) src: (usize, usize),
+    pub(crate) flip_bits: u8, // same as the `f` of a gridTile, bit 0 is x and bit 1 is y
+    pub(crate) alpha: f64,
+}
+
+impl LayerTile {
+    /// The top left corner of the sprite in the layer, in pixels
+    pub fn px(&self) -> (usize, usize) {
+        self.px
+    }
+
+    /// The top left corner of the sprite in the atlas, in pixels
+    pub fn src(&self) -> (usize, usize) {
+        self.src
+    }
+
+    /// If the sprite is flipped horizontally
+    pub fn flip_x(&self) -> bool {
+        self.flip_bits & 1 != 0
+    }
+
+    /// If the sprite is flipped vertically
+    pub fn flip_y(&self) -> bool {
+        self.flip_bits & 2 != 0
+    }
+
+    /// The opacity of the sprite, from 0 to 1
+    pub fn alpha(&self) -> f64 {
+        self.alpha
     }
 }
 
 /// Represents a single tile in a LDtk level
 #[derive(Clone, Default, Debug)]
 pub struct TileContents {
-    atlas_index: usize,
-    entity_name: Option<String>, // simply the name of the entity as the defs are stored in a raw file
-    entity_tag: Option<String>,
-    value: usize,
+    pub(crate) atlas_index: usize,
+    pub(crate) has_tile: bool,
+    pub(crate) flip_bits: u8, // same as the `f` of a gridTile, bit 0 is x and bit 1 is y
+    pub(crate) entity_name: Option<String>, // simply the name of the entity as the defs are stored in a raw file
+    pub(crate) entity_tags: Vec<String>,
+    pub(crate) entity_iid: Option<String>,
+    pub(crate) entity_fields: HashMap<String, FieldValue>,
+    pub(crate) entity_size: Option<(usize, usize)>,
+    pub(crate) value: usize,
 }
 
 impl TileContents {
-    /// The index in the tile atlas for this tile's sprite
+    /// The index in the tile atlas for this tile's sprite, counting sprites rather than pixels
+    /// so the spacing and padding of the atlas are left out, 16 sprites per row
     pub fn atlas_index(&self) -> usize {
         self.atlas_index
     }
 
+    /// If a tile was placed on the Ground layer for this tile.
+    /// When false the `atlas_index` is meaningless
+    pub fn has_tile(&self) -> bool {
+        self.has_tile
+    }
+
+    /// If the tile's sprite is flipped horizontally
+    pub fn flip_x(&self) -> bool {
+        self.flip_bits & 1 != 0
+    }
+
+    /// If the tile's sprite is flipped vertically
+    pub fn flip_y(&self) -> bool {
+        self.flip_bits & 2 != 0
+    }
+
     /// If the tile is blocked by the contents either, entity or terrain features
     pub fn value(&self) -> usize {
         self.value
@@ -113,22 +527,83 @@ impl TileContents {
         self.entity_name.as_deref()
     }
 
-    /// The tag associated with an entity.
-    /// Currently only supports 1 tag
+    /// The first tag of the entity, see `entity_tags` for all of them
     pub fn entity_tag(&self) -> Option<&str> {
-        self.entity_tag.as_deref()
+        self.entity_tags.first().map(|tag| tag.as_str())
+    }
+
+    /// The tags of the entity in the order of its definition
+    pub fn entity_tags(&self) -> &[String] {
+        &self.entity_tags
+    }
+
+    /// The unique id LDtk gave the entity, `None` for entities placed with `set_entity`
+    pub fn entity_iid(&self) -> Option<&str> {
+        self.entity_iid.as_deref()
+    }
+
+    /// The fields set on the entity in LDtk by their identifier, empty for entities placed with `set_entity`
+    pub fn entity_fields(&self) -> &HashMap<String, FieldValue> {
+        &self.entity_fields
+    }
+
+    /// The field named `identifier` of the entity
+    pub fn entity_field(&self, identifier: &str) -> Option<&FieldValue> {
+        self.entity_fields.get(identifier)
+    }
+
+    /// The width and height in pixels LDtk gave the entity, `None` for entities placed with `set_entity`
+    pub fn entity_size(&self) -> Option<(usize, usize)> {
+        self.entity_size
+    }
+
+    /// Places the sprite at `atlas_index` on this tile, clearing any flips
+    pub fn set_atlas_index(&mut self, atlas_index: usize) {
+        self.atlas_index = atlas_index;
+        self.has_tile = true;
+        self.flip_bits = 0;
+    }
+
+    /// Flips the tile's sprite on either axis
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_bits = flip_x as u8 | (flip_y as u8) << 1;
+    }
+
+    /// Removes the sprite from this tile
+    pub fn clear_tile(&mut self) {
+        self.atlas_index = 0;
+        self.has_tile = false;
+        self.flip_bits = 0;
+    }
+
+    /// Sets the IntGrid value of this tile, 0 being empty
+    pub fn set_value(&mut self, value: usize) {
+        self.value = value;
+    }
+
+    /// Places the entity named `entity_name` on this tile, or removes it with `None`.
+    /// The name is the same format as `entity_name`. The tags, iid, fields and size of the previous entity
+    /// are cleared, saving the map gives a newly placed entity the tags of its definition and a new iid.
+    pub fn set_entity(&mut self, entity_name: Option<&str>) {
+        self.entity_name = entity_name.map(|name| name.to_string());
+        self.entity_tags.clear();
+        self.entity_iid = None;
+        self.entity_fields.clear();
+        self.entity_size = None;
     }
 }
 
-const GROUND: &str = &"Ground";
-const ENTITIES: &str = &"Entities";
-const VALUES: &str = &"Values";
+pub(crate) const GROUND: &str = "Ground";
+pub(crate) const ENTITIES: &str = "Entities";
+pub(crate) const VALUES: &str = "Values";
 
 impl DesignMap {
     fn new() -> Self {
         Self {
             levels: HashMap::new(),
             tilesets: HashMap::new(),
+            toc: Vec::new(),
+            enums: HashMap::new(),
         }
     }
 
@@ -137,124 +612,303 @@ impl DesignMap {
         &self.levels
     }
 
+    /// The tilesets of the world by their uid
+    pub fn tilesets(&self) -> &HashMap<usize, DesignTileset> {
+        &self.tilesets
+    }
+
+    /// The entities listed in the table of contents of the project, with their world position and
+    /// fields, so they can be used without looking through every level
+    pub fn toc(&self) -> &[TocEntry] {
+        &self.toc
+    }
+
+    /// The enums of the project and the external enums it imports, by their identifier
+    pub fn enums(&self) -> &HashMap<String, EnumDef> {
+        &self.enums
+    }
+
+    /// The enum named `identifier`
+    pub fn enum_def(&self, identifier: &str) -> Option<&EnumDef> {
+        self.enums.get(identifier)
+    }
+
+    /// The definition of an enum field value, with its color and icon
+    pub fn resolve_enum(&self, value: &EnumValue) -> Option<&EnumValueDef> {
+        self.enum_def(value.enum_identifier())?.value(value.value())
+    }
+
+    /// The levels of the world, mutable so they can be edited and saved with `save_into`
+    pub fn levels_mut(&mut self) -> &mut HashMap<String, DesignLevel> {
+        &mut self.levels
+    }
+
     /// Loads the ldtk file located at path and creates a game-friendly DesignMap
     pub fn load(path: impl ToString) -> Self {
         let ldtk_world = get_raw_world(path.to_string());
+        Self::from_raw_world(&ldtk_world)
+    }
 
+    /// Creates the DesignMap out of an already deserialized ldtk file
+    pub(crate) fn from_raw_world(ldtk_world: &LDtk) -> Self {
         let mut design_map = DesignMap::new();
-        design_map.tilesets = tilesets(&ldtk_world);
+        design_map.tilesets = tilesets(&ldtk_world.defs);
+        design_map.enums = enum_defs(&ldtk_world.defs);
+        design_map.toc = toc_entries(
+            ldtk_world.toc.as_deref().unwrap_or_default(),
+            &ldtk_world.defs,
+        );
 
-        for level in ldtk_world.levels.iter() {
-            design_map.load_level(level);
+        for (order, level) in ldtk_world.levels.iter().enumerate() {
+            design_map.load_level(level, &ldtk_world.defs, order);
         }
 
         design_map
     }
 
     /// Creates a DesignLevel out of a ldtk level, `level` inserting into the DesignMap
-    fn load_level(&mut self, level: &Level) {
+    fn load_level(&mut self, level: &Level, defs: &Defs, order: usize) {
         let level_name = &level.identifier;
-        let mut new_design_level = DesignLevel::empty();
-        new_design_level.level_name = level.identifier.clone();
-        new_design_level.world_x = level.world_x / 8;
-        new_design_level.world_y = level.world_y / 8;
+        let new_design_level = design_level(level, defs, &self.tilesets, order);
 
-        if let Some(layer) = level
-            .layer_instances
-            .iter()
-            .find(|layer| layer.identifier.eq(GROUND))
+        if self
+            .levels
+            .insert(level_name.to_string(), new_design_level)
+            .is_some()
         {
-            new_design_level.width = layer.width;
-            new_design_level.height = layer.height;
-            new_design_level.grid_size_px = layer.grid_size;
-            new_design_level.level =
-                vec![TileContents::default(); new_design_level.width * new_design_level.height];
-
-            let tileset_id = layer.tileset_def_uid.unwrap();
-            new_design_level.tileset_name = match self.tilesets.get(&tileset_id) {
-                Some(val) => val.to_string(),
-                None => panic!(
-                    "Tileset ID: {} was not found in tileset collections.",
-                    tileset_id
-                ),
-            };
+            panic!("{} level already existed, will be overwritten and is undesired behavior. Please consult the ldtk file.", level_name)
+        }
+    }
+}
 
-            let grid_size = layer.grid_size;
-
-            // Since we should have matched on the "Ground" layer we have high confidence we will have a gridTiles vec full of data
-            if let Some(tiles) = &layer.grid_tiles {
-                for tile in tiles.iter() {
-                    let tile_index = gridpx_to_idx(
-                        (tile.grid_x() / grid_size, tile.grid_y() / grid_size),
-                        new_design_level.width,
-                    );
-                    new_design_level.level[tile_index].atlas_index = src_to_atlas_index(
-                        (tile.src_x(), tile.src_y()),
-                        new_design_level.grid_size_px(),
-                    );
-                }
+/// Converts a ldtk level into a DesignLevel, `order` being its position in the ldtk file
+pub(crate) fn design_level(
+    level: &Level,
+    defs: &Defs,
+    tilesets: &HashMap<usize, DesignTileset>,
+    order: usize,
+) -> DesignLevel {
+    let level_name = &level.identifier;
+    let mut new_design_level = DesignLevel::empty();
+    new_design_level.level_name = level.identifier.clone();
+    new_design_level.iid = level.iid.clone();
+    new_design_level.fields = field_instances(level.field_instances.iter().flatten().flatten());
+    new_design_level.world_px = (level.world_x, level.world_y);
+    new_design_level.world_depth = level.world_depth.unwrap_or_default();
+    new_design_level.order = order;
+
+    if let Some(layer) = level
+        .layer_instances
+        .iter()
+        .flatten()
+        .find(|layer| layer.identifier.eq(GROUND))
+    {
+        new_design_level.width = layer.width;
+        new_design_level.height = layer.height;
+        new_design_level.grid_size_px = layer.grid_size;
+        // world tiles are in the grid of the level, the same as `DesignMap::tile_at_world_tile`
+        let world_tiles = |world_px: i64| world_px.div_euclid(layer.grid_size.max(1) as i64);
+        new_design_level.world_x = world_tiles(level.world_x);
+        new_design_level.world_y = world_tiles(level.world_y);
+        let px = |size: Option<i64>, cells: usize| match size {
+            Some(size) => size.max(0) as usize,
+            None => cells * layer.grid_size,
+        };
+        new_design_level.size_px = (
+            px(level.px_wid, layer.width),
+            px(level.px_hei, layer.height),
+        );
+        new_design_level.level =
+            vec![TileContents::default(); new_design_level.width * new_design_level.height];
+
+        let tileset_id = layer.tileset_def_uid.unwrap();
+        new_design_level.tileset = match tilesets.get(&tileset_id) {
+            Some(val) => val.clone(),
+            None => panic!(
+                "Tileset ID: {} was not found in tileset collections.",
+                tileset_id
+            ),
+        };
+
+        let grid_size = layer.grid_size;
+
+        // Since we should have matched on the "Ground" layer we have high confidence we will have a gridTiles vec full of data
+        if let Some(tiles) = &layer.grid_tiles {
+            for tile in tiles.iter() {
+                let tile_index = gridpx_to_idx(
+                    (tile.grid_x() / grid_size, tile.grid_y() / grid_size),
+                    new_design_level.width,
+                );
+                let contents = &mut new_design_level.level[tile_index];
+                contents.atlas_index = new_design_level
+                    .tileset
+                    .src_to_atlas_index((tile.src_x(), tile.src_y()), grid_size);
+                contents.has_tile = true;
+                contents.flip_bits = tile.f as u8 & 3;
             }
-        } else {
-            panic!(
-                "Did not add a \"Ground\" layer to the level: {}",
-                level_name
-            );
         }
+    } else {
+        panic!(
+            "Did not add a \"Ground\" layer to the level: {}",
+            level_name
+        );
+    }
 
-        if let Some(layer) = level
-            .layer_instances
-            .iter()
-            .find(|layer| layer.identifier.eq(ENTITIES))
-        {
-            // Since we should have matched on the "Entities" layer we have high confidence we will have a Entities vec full of data
-            if let Some(entities) = &layer.entity_instances {
-                for entity in entities.iter() {
-                    let tile_index = gridpx_to_idx((entity.grid_x(), entity.grid_y()), layer.width);
-                    let new_name = entity.identifier.replace('_', " ").clone();
-                    new_design_level.level[tile_index].entity_name = Some(new_name);
-                    if let Some(tag) = entity.tags.first() {
-                        new_design_level.level[tile_index].entity_tag = Some(tag.to_string());
-                    }
-                }
+    if let Some(layer) = level
+        .layer_instances
+        .iter()
+        .flatten()
+        .find(|layer| layer.identifier.eq(ENTITIES))
+    {
+        // Since we should have matched on the "Entities" layer we have high confidence we will have a Entities vec full of data
+        if let Some(entities) = &layer.entity_instances {
+            for entity in entities.iter() {
+                let tile_index = gridpx_to_idx((entity.grid_x(), entity.grid_y()), layer.width);
+                let new_name = entity.identifier.replace('_', " ").clone();
+                new_design_level.level[tile_index].entity_name = Some(new_name);
+                new_design_level.level[tile_index].entity_tags = entity.tags.clone();
+                new_design_level.level[tile_index].entity_iid = entity.iid.clone();
+                new_design_level.level[tile_index].entity_fields =
+                    field_instances(entity.field_instances.iter().flatten().flatten());
+                new_design_level.level[tile_index].entity_size = entity
+                    .width
+                    .zip(entity.height)
+                    .map(|(width, height)| (width.max(0) as usize, height.max(0) as usize));
             }
         }
+    }
 
-        if let Some(layer) = level
-            .layer_instances
-            .iter()
-            .find(|layer| layer.identifier.eq(VALUES))
-        {
-            // Since we should have matched on the "Entities" layer we have high confidence we will have a Entities vec full of data
-            if let Some(values) = &layer.int_grid_csv {
-                for (idx, val) in values.iter().enumerate() {
-                    new_design_level.level[idx].value = *val;
-                }
+    if let Some(layer) = level
+        .layer_instances
+        .iter()
+        .flatten()
+        .find(|layer| layer.identifier.eq(VALUES))
+    {
+        new_design_level.int_grid_values = int_grid_values(defs, layer.layer_def_uid);
+
+        // Since we should have matched on the "Values" layer we have high confidence we will have a intGridCsv vec full of data
+        if let Some(values) = &layer.int_grid_csv {
+            for (idx, val) in values.iter().enumerate() {
+                new_design_level.level[idx].value = *val;
             }
         }
+    }
 
-        if self
-            .levels
-            .insert(level_name.to_string(), new_design_level)
-            .is_some()
-        {
-            panic!("{} level already existed, will be overwritten and is undesired behavior. Please consult the ldtk file.", level_name)
+    // LDtk lists the top layer first
+    for layer in level.layer_instances.iter().flatten().rev() {
+        if layer.identifier.eq(GROUND) {
+            new_design_level.ground_depth = new_design_level.tile_layers.len();
+        } else if let Some(tile_layer) = tile_layer(layer, tilesets) {
+            new_design_level.tile_layers.push(tile_layer);
         }
     }
+
+    new_design_level
 }
 
-/// Creates the connection of tileset ids to their names
-fn tilesets(data: &LDtk) -> HashMap<usize, String> {
-    data.defs
-        .tilesets
+/// The sprites of `layer`, `None` when it is hidden or has no sprites
+fn tile_layer(
+    layer: &LayerInstance,
+    tilesets: &HashMap<usize, DesignTileset>,
+) -> Option<TileLayer> {
+    if !layer.visible {
+        return None;
+    }
+    let auto_tiles: Vec<GridTile> = layer
+        .auto_layer_tiles
         .iter()
-        .map(|tileset| (tileset.uid, tileset.identifier.clone()))
+        .flatten()
+        .flatten()
+        .filter_map(|tile| serde_json::from_value(tile.clone()).ok())
+        .collect();
+    let tiles: Vec<LayerTile> = layer
+        .grid_tiles
+        .iter()
+        .flatten()
+        .chain(auto_tiles.iter())
+        .map(|tile| LayerTile {
+            px: tile.px,
+            src: tile.src,
+            flip_bits: tile.f as u8 & 3,
+            alpha: tile.a,
+        })
+        .collect();
+    if tiles.is_empty() {
+        return None;
+    }
+
+    let tileset_id = layer.tileset_def_uid.unwrap_or_default();
+    let tileset = match tilesets.get(&tileset_id) {
+        Some(val) => val.clone(),
+        None => panic!(
+            "Tileset ID: {} was not found in tileset collections.",
+            tileset_id
+        ),
+    };
+    Some(TileLayer {
+        identifier: layer.identifier.clone(),
+        tileset,
+        grid_size_px: layer.grid_size,
+        offset_px: (
+            layer.px_total_offset_x.unwrap_or_default(),
+            layer.px_total_offset_y.unwrap_or_default(),
+        ),
+        opacity: layer.opacity.unwrap_or(1.0),
+        tiles,
+    })
+}
+
+/// Reads the IntGrid values defined for the layer with the uid `layer_def_uid`
+pub(crate) fn int_grid_values(defs: &Defs, layer_def_uid: Option<i64>) -> Vec<IntGridValue> {
+    defs.layers
+        .iter()
+        .flatten()
+        .find(|layer| layer.uid.is_some() && layer.uid == layer_def_uid)
+        .and_then(|layer| layer.int_grid_values.as_ref())
         .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|value| {
+            Some(IntGridValue {
+                value: value["value"].as_u64()? as usize,
+                identifier: value["identifier"].as_str().map(|id| id.to_string()),
+                color: value["color"].as_str().unwrap_or("#000000").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Creates the connection of tileset ids to their tilesets
+pub(crate) fn tilesets(defs: &Defs) -> HashMap<usize, DesignTileset> {
+    let size = |value: Option<i64>| value.unwrap_or_default().max(0) as usize;
+    defs.tilesets
+        .iter()
+        .map(|tileset| {
+            let design_tileset = DesignTileset {
+                identifier: tileset.identifier.clone(),
+                uid: tileset.uid,
+                rel_path: tileset.rel_path.clone(),
+                embed_atlas: tileset.embed_atlas.clone(),
+                width_px: size(tileset.px_wid),
+                height_px: size(tileset.px_hei),
+                tile_grid_size: size(tileset.tile_grid_size),
+                spacing: size(tileset.spacing),
+                padding: size(tileset.padding),
+            };
+            (tileset.uid, design_tileset)
+        })
         .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use super::DesignMap;
+    use crate::{
+        project_builder::{LevelBuilder, ProjectBuilder},
+        test_support::TempPath,
+    };
 
     #[test]
     fn test_load_world_with_different_sized_levels() {
@@ -286,15 +940,49 @@ mod tests {
     fn test_load_levels_with_different_tilesets() {
         let world = DesignMap::load("./tests/testmaps/two_tileatlases.ldtk".to_string());
         assert_eq!(
-            world.levels[&"Level_0".to_string()].tileset_name,
-            "Forest".to_string()
+            world.levels[&"Level_0".to_string()].tileset_name(),
+            "Forest"
         );
         assert_eq!(
-            world.levels[&"Level_1".to_string()].tileset_name,
-            "SecondTileset".to_string()
+            world.levels[&"Level_1".to_string()].tileset_name(),
+            "SecondTileset"
         );
     }
 
+    #[test]
+    fn test_coordinate_access() {
+        let world = DesignMap::load("./tests/testmaps/entities.ldtk".to_string());
+        let level = &world.levels["Level_0"];
+        let (width, height) = (level.width(), level.height());
+
+        assert_eq!(
+            level.get(3, 0).and_then(|tile| tile.entity_name()),
+            Some("Monster1")
+        );
+        assert!(level.get(width - 1, height - 1).is_some());
+        // does not wrap into the next row
+        assert!(level.get(width, 0).is_none());
+        assert!(level.get(0, height).is_none());
+        assert!(!level.in_bounds(width, 0));
+
+        let ((x, y), _) = level.iter_coords().nth(width + 2).unwrap();
+        assert_eq!((x, y), (2, 1));
+        assert_eq!(level.rows().count(), height);
+        assert!(level.rows().all(|row| row.len() == width));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let world = DesignMap::load("./tests/testmaps/entities.ldtk".to_string());
+        let level = &world.levels["Level_0"];
+
+        let corner: Vec<_> = level.neighbours4(0, 0).map(|(xy, _)| xy).collect();
+        assert_eq!(corner, vec![(1, 0), (0, 1)]);
+        assert_eq!(level.neighbours8(0, 0).count(), 3);
+        assert_eq!(level.neighbours4(1, 1).count(), 4);
+        assert_eq!(level.neighbours8(1, 1).count(), 8);
+    }
+
     #[test]
     #[should_panic]
     /// Tests that the world cannot be opened since it is empty
@@ -308,4 +996,28 @@ mod tests {
     fn test_duplicate_level_name() {
         let _ = DesignMap::load("./tests/testmaps/bad_names.ldtk".to_string());
     }
+
+    #[test]
+    fn test_atlas_index_with_spacing_and_padding() {
+        let path = TempPath::new("spaced.ldtk");
+        ProjectBuilder::new()
+            .tileset("Spaced", "../atlas/spaced.png", (37, 28), 8)
+            .tiles_layer("Ground", 8, "Spaced")
+            .level(LevelBuilder::new("Level_0", (16, 8)).tile("Ground", (1, 0), 0))
+            .save(&path);
+        // 8 px sprites with 1 px between them and 2 px around the atlas, the tile showing the
+        // sprite in the third column of the second row
+        let mut project: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        project["defs"]["tilesets"][0]["spacing"] = json!(1);
+        project["defs"]["tilesets"][0]["padding"] = json!(2);
+        project["levels"][0]["layerInstances"][0]["gridTiles"][0]["src"] = json!([20, 11]);
+        std::fs::write(&path, project.to_string()).unwrap();
+
+        let world = DesignMap::load(&path);
+        let level = &world.levels["Level_0"];
+        let atlas_index = level.level[1].atlas_index();
+        assert_eq!(atlas_index, 2 + 16);
+        assert_eq!(level.tileset().atlas_index_to_src(atlas_index, 8), (20, 11));
+    }
 }
@@ -0,0 +1,536 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::design_map::{DesignLevel, DesignMap, TileContents};
+
+/// The sprite on a tile as compared by `diff`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileSprite {
+    /// The index of the sprite in the atlas, see `TileContents::atlas_index`
+    pub atlas_index: usize,
+    /// If the sprite is flipped horizontally
+    pub flip_x: bool,
+    /// If the sprite is flipped vertically
+    pub flip_y: bool,
+}
+
+impl TileSprite {
+    /// The sprite of `tile`, `None` when it has none
+    fn of(tile: &TileContents) -> Option<Self> {
+        tile.has_tile.then(|| TileSprite {
+            atlas_index: tile.atlas_index,
+            flip_x: tile.flip_x(),
+            flip_y: tile.flip_y(),
+        })
+    }
+}
+
+impl fmt::Display for TileSprite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.flip_x, self.flip_y) {
+            (false, false) => write!(f, "{}", self.atlas_index),
+            (true, false) => write!(f, "{} flipped on x", self.atlas_index),
+            (false, true) => write!(f, "{} flipped on y", self.atlas_index),
+            (true, true) => write!(f, "{} flipped on x and y", self.atlas_index),
+        }
+    }
+}
+
+/// A single difference between two versions of a map found by `diff`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// A level only in the new map
+    LevelAdded {
+        /// The name of the level
+        level: String,
+    },
+    /// A level only in the old map
+    LevelRemoved {
+        /// The name of the level
+        level: String,
+    },
+    /// A level with the same iid but another name
+    LevelRenamed {
+        /// The old name of the level
+        from: String,
+        /// The new name of the level
+        to: String,
+    },
+    /// A level whose width or height in tiles changed
+    LevelResized {
+        /// The name of the level in the new map
+        level: String,
+        /// The old width and height
+        from: (usize, usize),
+        /// The new width and height
+        to: (usize, usize),
+    },
+    /// A tile whose sprite or flips changed, `None` being no sprite
+    TileChanged {
+        /// The name of the level in the new map
+        level: String,
+        /// The coordinates of the tile
+        xy: (usize, usize),
+        /// The old sprite
+        from: Option<TileSprite>,
+        /// The new sprite
+        to: Option<TileSprite>,
+    },
+    /// A tile whose IntGrid value changed
+    ValueChanged {
+        /// The name of the level in the new map
+        level: String,
+        /// The coordinates of the tile
+        xy: (usize, usize),
+        /// The old value
+        from: usize,
+        /// The new value
+        to: usize,
+    },
+    /// An entity only in the new map
+    EntityAdded {
+        /// The name of the level in the new map
+        level: String,
+        /// The name of the entity
+        name: String,
+        /// The tile the entity is on
+        xy: (usize, usize),
+    },
+    /// An entity only in the old map
+    EntityRemoved {
+        /// The name of the level in the new map
+        level: String,
+        /// The name of the entity
+        name: String,
+        /// The tile the entity was on
+        xy: (usize, usize),
+    },
+    /// An entity with the same iid on another tile
+    EntityMoved {
+        /// The name of the level in the new map
+        level: String,
+        /// The name of the entity
+        name: String,
+        /// The old tile of the entity
+        from: (usize, usize),
+        /// The new tile of the entity
+        to: (usize, usize),
+    },
+}
+
+impl Change {
+    /// The level the change is in, the new name for levels that were renamed
+    pub fn level(&self) -> &str {
+        match self {
+            Change::LevelAdded { level }
+            | Change::LevelRemoved { level }
+            | Change::LevelRenamed { to: level, .. }
+            | Change::LevelResized { level, .. }
+            | Change::TileChanged { level, .. }
+            | Change::ValueChanged { level, .. }
+            | Change::EntityAdded { level, .. }
+            | Change::EntityRemoved { level, .. }
+            | Change::EntityMoved { level, .. } => level,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tile = |sprite: &Option<TileSprite>| match sprite {
+            Some(sprite) => sprite.to_string(),
+            None => "none".to_string(),
+        };
+        match self {
+            Change::LevelAdded { level } => write!(f, "{}: level added", level),
+            Change::LevelRemoved { level } => write!(f, "{}: level removed", level),
+            Change::LevelRenamed { from, to } => write!(f, "{}: level renamed from {}", to, from),
+            Change::LevelResized { level, from, to } => write!(
+                f,
+                "{}: resized from {}x{} to {}x{}",
+                level, from.0, from.1, to.0, to.1
+            ),
+            Change::TileChanged {
+                level,
+                xy,
+                from,
+                to,
+            } => write!(
+                f,
+                "{}: tile at {:?} changed from {} to {}",
+                level,
+                xy,
+                tile(from),
+                tile(to)
+            ),
+            Change::ValueChanged {
+                level,
+                xy,
+                from,
+                to,
+            } => write!(
+                f,
+                "{}: value at {:?} changed from {} to {}",
+                level, xy, from, to
+            ),
+            Change::EntityAdded { level, name, xy } => {
+                write!(f, "{}: {} added at {:?}", level, name, xy)
+            }
+            Change::EntityRemoved { level, name, xy } => {
+                write!(f, "{}: {} removed from {:?}", level, name, xy)
+            }
+            Change::EntityMoved {
+                level,
+                name,
+                from,
+                to,
+            } => write!(f, "{}: {} moved from {:?} to {:?}", level, name, from, to),
+        }
+    }
+}
+
+/// Every difference between two versions of a map, see `diff`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapDiff {
+    changes: Vec<Change>,
+}
+
+impl MapDiff {
+    /// The changes, grouped by level in the order of their names
+    pub fn changes(&self) -> &[Change] {
+        self.changes.as_ref()
+    }
+
+    /// Whether the maps are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// A short overview for reviewers, one line per level change and entity with
+    /// tile and value changes counted per level
+    pub fn summary(&self) -> String {
+        let mut lines = vec![];
+        let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for change in self.changes.iter() {
+            match change {
+                Change::TileChanged { level, .. } => counts.entry(level).or_default().0 += 1,
+                Change::ValueChanged { level, .. } => counts.entry(level).or_default().1 += 1,
+                _ => lines.push(change.to_string()),
+            }
+        }
+        for (level, (tiles, values)) in counts {
+            let mut parts = vec![];
+            if tiles > 0 {
+                parts.push(format!("{} {} changed", tiles, plural(tiles, "tile")));
+            }
+            if values > 0 {
+                parts.push(format!("{} {} changed", values, plural(values, "value")));
+            }
+            lines.push(format!("{}: {}", level, parts.join(", ")));
+        }
+
+        if lines.is_empty() {
+            return "No changes".to_string();
+        }
+        lines.join("\n")
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+impl fmt::Display for MapDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Compares two versions of a map, e.g. before and after a commit.
+///
+/// Levels are matched by their iid, or by name when they have none, so renames are found.
+/// Entities are matched by their iid as well, entities without one by their tile.
+/// Tiles of resized levels are compared where the old and new sizes overlap.
+pub fn diff(old: &DesignMap, new: &DesignMap) -> MapDiff {
+    let key = |level: &DesignLevel| match level.iid() {
+        Some(iid) => iid.to_string(),
+        None => format!("name:{}", level.name()),
+    };
+    let old_levels: HashMap<String, &DesignLevel> = old
+        .levels
+        .values()
+        .map(|level| (key(level), level))
+        .collect();
+    let new_levels: HashMap<String, &DesignLevel> = new
+        .levels
+        .values()
+        .map(|level| (key(level), level))
+        .collect();
+
+    let mut changes = vec![];
+    for (key, old_level) in old_levels.iter() {
+        if !new_levels.contains_key(key) {
+            changes.push(Change::LevelRemoved {
+                level: old_level.name().to_string(),
+            });
+        }
+    }
+    for (key, new_level) in new_levels.iter() {
+        match old_levels.get(key) {
+            Some(old_level) => diff_level(old_level, new_level, &mut changes),
+            None => changes.push(Change::LevelAdded {
+                level: new_level.name().to_string(),
+            }),
+        }
+    }
+
+    // stable sort keeps the changes of a level in the order they were found
+    changes.sort_by(|a, b| a.level().cmp(b.level()));
+    MapDiff { changes }
+}
+
+fn diff_level(old: &DesignLevel, new: &DesignLevel, changes: &mut Vec<Change>) {
+    let level = new.name().to_string();
+    if old.name() != new.name() {
+        changes.push(Change::LevelRenamed {
+            from: old.name().to_string(),
+            to: level.clone(),
+        });
+    }
+    if (old.width, old.height) != (new.width, new.height) {
+        changes.push(Change::LevelResized {
+            level: level.clone(),
+            from: (old.width, old.height),
+            to: (new.width, new.height),
+        });
+    }
+
+    for y in 0..old.height.min(new.height) {
+        for x in 0..old.width.min(new.width) {
+            let (Some(old_tile), Some(new_tile)) = (old.get(x, y), new.get(x, y)) else {
+                continue;
+            };
+            let (from, to) = (TileSprite::of(old_tile), TileSprite::of(new_tile));
+            if from != to {
+                changes.push(Change::TileChanged {
+                    level: level.clone(),
+                    xy: (x, y),
+                    from,
+                    to,
+                });
+            }
+            if old_tile.value != new_tile.value {
+                changes.push(Change::ValueChanged {
+                    level: level.clone(),
+                    xy: (x, y),
+                    from: old_tile.value,
+                    to: new_tile.value,
+                });
+            }
+        }
+    }
+
+    diff_entities(old, new, &level, changes);
+}
+
+/// An entity found on a level, keyed by its iid or by its tile when it has none
+type Entities<'a> = BTreeMap<String, ((usize, usize), &'a str)>;
+
+fn entities(level: &DesignLevel) -> Entities<'_> {
+    level
+        .iter_coords()
+        .filter_map(|(xy, tile)| {
+            let name = tile.entity_name()?;
+            let key = match tile.entity_iid() {
+                Some(iid) => iid.to_string(),
+                None => format!("{:?}:{}", xy, name),
+            };
+            Some((key, (xy, name)))
+        })
+        .collect()
+}
+
+fn diff_entities(old: &DesignLevel, new: &DesignLevel, level: &str, changes: &mut Vec<Change>) {
+    let old_entities = entities(old);
+    let new_entities = entities(new);
+
+    for (key, (xy, name)) in old_entities.iter() {
+        match new_entities.get(key) {
+            Some((new_xy, new_name)) if new_name == name => {
+                if new_xy != xy {
+                    changes.push(Change::EntityMoved {
+                        level: level.to_string(),
+                        name: name.to_string(),
+                        from: *xy,
+                        to: *new_xy,
+                    });
+                }
+            }
+            _ => changes.push(Change::EntityRemoved {
+                level: level.to_string(),
+                name: name.to_string(),
+                xy: *xy,
+            }),
+        }
+    }
+    for (key, (xy, name)) in new_entities.iter() {
+        let same = old_entities
+            .get(key)
+            .is_some_and(|(_, old_name)| old_name == name);
+        if !same {
+            changes.push(Change::EntityAdded {
+                level: level.to_string(),
+                name: name.to_string(),
+                xy: *xy,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change, TileSprite};
+    use crate::{
+        design_map::{DesignMap, TileContents},
+        project_builder::LevelBuilder,
+        test_support::forest_project,
+    };
+
+    fn sprite(atlas_index: usize, flip_x: bool, flip_y: bool) -> Option<TileSprite> {
+        Some(TileSprite {
+            atlas_index,
+            flip_x,
+            flip_y,
+        })
+    }
+
+    fn world() -> DesignMap {
+        forest_project(8, &["wall"])
+            .level(
+                LevelBuilder::new("Level_0", (32, 32))
+                    .tile("Ground", (0, 0), 1)
+                    .entity("Entities", (1, 1), "Monster1")
+                    .entity("Entities", (2, 2), "Monster1"),
+            )
+            .level(LevelBuilder::new("Level_1", (16, 16)))
+            .build()
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let old = world();
+        let changes = diff(&old, &old);
+        assert!(changes.is_empty());
+        assert_eq!(changes.summary(), "No changes");
+    }
+
+    #[test]
+    fn test_level_changes() {
+        let old = world();
+        let mut new = old.clone();
+        new.levels.remove("Level_0");
+        let mut level = new.levels.remove("Level_1").unwrap();
+        level.level_name = "Cave".to_string();
+        level.width = 3;
+        level.level = vec![TileContents::default(); 6];
+        new.levels.insert("Cave".to_string(), level);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.changes(),
+            &[
+                Change::LevelRenamed {
+                    from: "Level_1".to_string(),
+                    to: "Cave".to_string()
+                },
+                Change::LevelResized {
+                    level: "Cave".to_string(),
+                    from: (2, 2),
+                    to: (3, 2)
+                },
+                Change::LevelRemoved {
+                    level: "Level_0".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_and_entity_changes() {
+        let old = world();
+        let mut new = old.clone();
+        let level = new.levels.get_mut("Level_0").unwrap();
+        level.get_mut(0, 0).unwrap().set_atlas_index(2);
+        level.get_mut(3, 0).unwrap().set_value(1);
+        // move the first monster keeping its iid, replace the second one
+        let moved = level.get(1, 1).unwrap().clone();
+        *level.get_mut(1, 3).unwrap() = moved;
+        level.get_mut(1, 1).unwrap().set_entity(None);
+        level.get_mut(2, 2).unwrap().set_entity(Some("Monster1"));
+
+        let changes = diff(&old, &new);
+        let found = |change: Change| changes.changes().contains(&change);
+        assert!(found(Change::TileChanged {
+            level: "Level_0".to_string(),
+            xy: (0, 0),
+            from: sprite(1, false, false),
+            to: sprite(2, false, false)
+        }));
+        assert!(found(Change::ValueChanged {
+            level: "Level_0".to_string(),
+            xy: (3, 0),
+            from: 0,
+            to: 1
+        }));
+        assert!(found(Change::EntityMoved {
+            level: "Level_0".to_string(),
+            name: "Monster1".to_string(),
+            from: (1, 1),
+            to: (1, 3)
+        }));
+        assert!(found(Change::EntityRemoved {
+            level: "Level_0".to_string(),
+            name: "Monster1".to_string(),
+            xy: (2, 2)
+        }));
+        assert!(found(Change::EntityAdded {
+            level: "Level_0".to_string(),
+            name: "Monster1".to_string(),
+            xy: (2, 2)
+        }));
+        assert_eq!(changes.changes().len(), 5);
+
+        let summary = changes.summary();
+        assert!(summary.contains("Level_0: Monster1 moved from (1, 1) to (1, 3)"));
+        assert!(summary.contains("Level_0: 1 tile changed, 1 value changed"));
+    }
+
+    #[test]
+    fn test_flip_changes() {
+        let old = world();
+        let mut new = old.clone();
+        let level = new.levels.get_mut("Level_0").unwrap();
+        level.get_mut(0, 0).unwrap().set_flip(true, false);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.changes(),
+            &[Change::TileChanged {
+                level: "Level_0".to_string(),
+                xy: (0, 0),
+                from: sprite(1, false, false),
+                to: sprite(1, true, false)
+            }]
+        );
+        assert_eq!(
+            changes.changes()[0].to_string(),
+            "Level_0: tile at (0, 0) changed from 1 to 1 flipped on x"
+        );
+    }
+}
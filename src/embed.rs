@@ -0,0 +1,155 @@
+use std::fs;
+
+use crate::{
+    compression::decompress,
+    design_map::DesignMap,
+    ldtk_helpers::{get_raw_world, try_parse_raw_world},
+};
+
+/// Loads a map embedded in the binary at compile time, needing no file IO at runtime.
+///
+/// The path is relative to the file calling the macro, like `include_bytes!`.
+/// Projects saving their levels to separate files must be baked with `ldtk_map::bake` first.
+/// ```ignore
+/// let map = ldtk_map::embed!("../maps/world.ldtk");
+/// ```
+#[macro_export]
+macro_rules! embed {
+    ($path:expr) => {
+        $crate::prelude::DesignMap::from_static(include_bytes!($path))
+    };
+}
+
+impl DesignMap {
+    /// Creates the DesignMap out of the contents of a ldtk file, either as saved by LDtk or baked
+    /// with `ldtk_map::bake` and possibly compressed, usually included in the binary through `ldtk_map::embed!`
+    pub fn from_static(contents: &'static [u8]) -> Self {
+        let result = decompress(contents, "the embedded map").and_then(|contents| {
+            try_parse_raw_world(&contents, "the embedded map", |rel_path| {
+                Err(format!(
+                    "{} is saved in its own file which can not be embedded, \
+                     bake the map with ldtk_map::bake first",
+                    rel_path
+                ))
+            })
+        });
+        match result {
+            Ok(ldtk_world) => Self::from_raw_world(&ldtk_world),
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Writes the ldtk file located at path into a single compact file at `out_path`, ready to be
+/// embedded with `ldtk_map::embed!` or shipped with the game.
+///
+/// Levels saved in separate files are brought into the baked file, maps saved by older LDtk
+/// versions are migrated and the json is written without any whitespace. The baked file is
+/// still a valid ldtk file.
+pub fn bake(path: impl ToString, out_path: impl ToString) {
+    let out_path = out_path.to_string();
+    let mut ldtk_world = get_raw_world(path.to_string());
+    ldtk_world.external_levels = Some(false);
+    for level in ldtk_world.levels.iter_mut() {
+        level.external_rel_path = None;
+    }
+
+    let contents = match serde_json::to_vec(&ldtk_world) {
+        Ok(contents) => contents,
+        Err(e) => panic!(
+            "Could not serialize the world for {} | Error: {}",
+            out_path, e
+        ),
+    };
+    if let Err(e) = fs::write(&out_path, contents) {
+        panic!(
+            "Should have been able to write the file: {} | Error: {}",
+            out_path, e
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bake;
+    use crate::{
+        design_map::{DesignMap, TileContents},
+        project_builder::LevelBuilder,
+        test_support::{forest_project, TempPath},
+    };
+
+    /// Checks every tile of `embedded` has the sprite, value and entity it has in `loaded`
+    fn assert_same_tiles(embedded: &DesignMap, loaded: &DesignMap) {
+        assert_eq!(embedded.levels().len(), loaded.levels().len());
+        for (name, level) in loaded.levels() {
+            let embedded_level = &embedded.levels()[name];
+            assert_eq!(
+                (embedded_level.width(), embedded_level.height()),
+                (level.width(), level.height())
+            );
+            for (xy, tile) in level.iter_coords() {
+                let embedded_tile = embedded_level.get(xy.0, xy.1).unwrap();
+                let contents = |tile: &TileContents| {
+                    (
+                        tile.has_tile().then_some(tile.atlas_index()),
+                        (tile.flip_x(), tile.flip_y()),
+                        tile.value(),
+                        tile.entity_name().map(|name| name.to_string()),
+                        tile.entity_iid().map(|iid| iid.to_string()),
+                    )
+                };
+                assert_eq!(
+                    contents(embedded_tile),
+                    contents(tile),
+                    "{} at {:?}",
+                    name,
+                    xy
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_embed() {
+        let map = crate::embed!("../tests/testmaps/two_tileatlases.ldtk");
+        let loaded = DesignMap::load("./tests/testmaps/two_tileatlases.ldtk");
+        assert_same_tiles(&map, &loaded);
+
+        let map = crate::embed!("../tests/testmaps/entities.ldtk");
+        let loaded = DesignMap::load("./tests/testmaps/entities.ldtk");
+        assert!(loaded.levels()["Level_0"]
+            .iter_coords()
+            .any(|(_, tile)| tile.entity_name().is_some()));
+        assert_same_tiles(&map, &loaded);
+    }
+
+    #[test]
+    fn test_bake_external_levels() {
+        let dir = TempPath::dir("bake");
+        let path = dir.join("world.ldtk");
+        let baked_path = dir.join("baked.ldtk");
+        forest_project(8, &["wall"])
+            .external_levels(true)
+            .level(LevelBuilder::new("Level_0", (16, 16)).value("Values", (1, 1), 1))
+            .save(&path);
+
+        bake(&path, &baked_path);
+        let baked: &'static [u8] = std::fs::read(&baked_path).unwrap().leak();
+        let map = DesignMap::from_static(baked);
+        assert_eq!(map.levels()["Level_0"].get(1, 1).unwrap().value(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "bake the map")]
+    fn test_embed_external_levels_fails() {
+        let dir = TempPath::dir("embed");
+        let path = dir.join("world.ldtk");
+        forest_project(8, &[])
+            .external_levels(true)
+            .level(LevelBuilder::new("Level_0", (16, 16)))
+            .save(&path);
+
+        let contents: &'static [u8] = std::fs::read(&path).unwrap().leak();
+        DesignMap::from_static(contents);
+    }
+}
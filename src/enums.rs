@@ -0,0 +1,311 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde_json::Value;
+
+use crate::{fields::TileRect, ldtk_structs::Defs};
+
+/// An enum defined in LDtk, either in the project or imported from an external file
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumDef {
+    identifier: String,
+    uid: usize,
+    values: Vec<EnumValueDef>,
+    icon_tileset_uid: Option<usize>,
+    external_rel_path: Option<String>,
+    tags: Vec<String>,
+}
+
+/// One of the values of an `EnumDef`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumValueDef {
+    id: String,
+    color: String,
+    tile: Option<TileRect>,
+}
+
+/// The value of an enum field, naming the enum it belongs to
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnumValue {
+    enum_identifier: String,
+    value: String,
+}
+
+impl EnumDef {
+    /// The name of the enum
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_ref()
+    }
+
+    /// The unique id LDtk gave the enum
+    pub fn uid(&self) -> usize {
+        self.uid
+    }
+
+    /// The values of the enum in the order of the LDtk project
+    pub fn values(&self) -> &[EnumValueDef] {
+        &self.values
+    }
+
+    /// The value named `id`
+    pub fn value(&self, id: &str) -> Option<&EnumValueDef> {
+        self.values.iter().find(|value| value.id == id)
+    }
+
+    /// The uid of the tileset the icons of the values are picked from
+    pub fn icon_tileset_uid(&self) -> Option<usize> {
+        self.icon_tileset_uid
+    }
+
+    /// Whether the enum is imported from an external file instead of being defined in LDtk
+    pub fn is_external(&self) -> bool {
+        self.external_rel_path.is_some()
+    }
+
+    /// The path of the file an external enum is imported from, relative to the project
+    pub fn external_rel_path(&self) -> Option<&str> {
+        self.external_rel_path.as_deref()
+    }
+
+    /// The tags given to the enum in LDtk
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Checks the values of the enum are exactly `variants`, the names of the variants of the
+    /// matching Rust enum, describing every value missing on either side otherwise.
+    ///
+    /// Meant to be called once after loading so a renamed value fails at startup
+    /// instead of when an entity using it is met.
+    pub fn check_variants(&self, variants: &[&str]) -> Result<(), String> {
+        let missing_in_rust: Vec<&str> = self
+            .values
+            .iter()
+            .map(|value| value.id.as_str())
+            .filter(|id| !variants.contains(id))
+            .collect();
+        let missing_in_ldtk: Vec<&str> = variants
+            .iter()
+            .copied()
+            .filter(|variant| self.value(variant).is_none())
+            .collect();
+
+        let mut problems = vec![];
+        if !missing_in_rust.is_empty() {
+            problems.push(format!(
+                "values missing in the Rust enum: {}",
+                missing_in_rust.join(", ")
+            ));
+        }
+        if !missing_in_ldtk.is_empty() {
+            problems.push(format!(
+                "variants missing in LDtk: {}",
+                missing_in_ldtk.join(", ")
+            ));
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(format!(
+                "LDtk enum {} does not match | {}",
+                self.identifier,
+                problems.join(" | ")
+            )),
+        }
+    }
+}
+
+impl EnumValueDef {
+    /// The name of the value
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// The color of the value in LDtk as a hex string, e.g. `#BE4A2F`
+    pub fn color(&self) -> &str {
+        self.color.as_ref()
+    }
+
+    /// The icon of the value picked in the icon tileset of the enum
+    pub fn tile(&self) -> Option<TileRect> {
+        self.tile
+    }
+}
+
+impl EnumValue {
+    pub(crate) fn new(enum_identifier: &str, value: &str) -> Self {
+        Self {
+            enum_identifier: enum_identifier.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// The name of the enum the value belongs to
+    pub fn enum_identifier(&self) -> &str {
+        self.enum_identifier.as_ref()
+    }
+
+    /// The name of the value
+    pub fn value(&self) -> &str {
+        self.value.as_ref()
+    }
+
+    /// Converts the value into a Rust enum implementing `FromStr` over the same names
+    pub fn parse<T: FromStr>(&self) -> Result<T, T::Err> {
+        self.value.parse()
+    }
+}
+
+/// Reads the enums of the project and the external enums it imports, by their identifier
+pub(crate) fn enum_defs(defs: &Defs) -> HashMap<String, EnumDef> {
+    enum_def_list(defs)
+        .into_iter()
+        .map(|def| (def.identifier.clone(), def))
+        .collect()
+}
+
+/// Reads the enums of the project followed by the external enums, in the order of the project
+pub(crate) fn enum_def_list(defs: &Defs) -> Vec<EnumDef> {
+    defs.enums
+        .iter()
+        .flatten()
+        .chain(defs.external_enums.iter().flatten())
+        .flatten()
+        .filter_map(enum_def)
+        .collect()
+}
+
+fn enum_def(def: &Value) -> Option<EnumDef> {
+    let size = |value: &Value| value.as_u64().map(|n| n as usize);
+    let values = def["values"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|value| {
+            Some(EnumValueDef {
+                id: value["id"].as_str()?.to_string(),
+                color: format!("#{:06X}", value["color"].as_u64().unwrap_or_default()),
+                tile: TileRect::from_json(&value["tileRect"]),
+            })
+        })
+        .collect();
+
+    Some(EnumDef {
+        identifier: def["identifier"].as_str()?.to_string(),
+        uid: size(&def["uid"]).unwrap_or_default(),
+        values,
+        icon_tileset_uid: size(&def["iconTilesetUid"]),
+        external_rel_path: def["externalRelPath"].as_str().map(|path| path.to_string()),
+        tags: def["tags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| Some(tag.as_str()?.to_string()))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{design_map::DesignMap, fields::FieldValue, test_support::patched};
+
+    #[derive(Debug, PartialEq)]
+    enum ItemKind {
+        Sword,
+        Potion,
+    }
+
+    impl std::str::FromStr for ItemKind {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "Sword" => Ok(ItemKind::Sword),
+                "Potion" => Ok(ItemKind::Potion),
+                _ => Err(format!("unknown item {}", s)),
+            }
+        }
+    }
+
+    fn load_project() -> DesignMap {
+        let path = patched("./tests/testmaps/entities.ldtk", "enums.ldtk", |project| {
+            project["jsonVersion"] = json!("1.5.3");
+            project["defs"]["enums"] = json!([{
+                "identifier": "ItemKind",
+                "uid": 30,
+                "iconTilesetUid": 1,
+                "externalRelPath": null,
+                "tags": ["loot"],
+                "values": [
+                    { "id": "Sword", "color": 12476975, "tileRect": { "tilesetUid": 1, "x": 8, "y": 0, "w": 8, "h": 8 } },
+                    { "id": "Potion", "color": 255, "tileRect": null }
+                ]
+            }]);
+            project["defs"]["externalEnums"] = json!([{
+                "identifier": "Biome",
+                "uid": 31,
+                "iconTilesetUid": null,
+                "externalRelPath": "biomes.cdb",
+                "tags": [],
+                "values": [{ "id": "Forest", "color": 0, "tileRect": null }]
+            }]);
+            project["defs"]["entities"][0]["exportToToc"] = json!(true);
+            project["defs"]["entities"][0]["fieldDefs"] =
+                json!([{ "identifier": "loot", "__type": "Array<LocalEnum.ItemKind>" }]);
+            project["toc"] = json!([{
+                "identifier": "Monster1",
+                "instancesData": [{
+                    "iids": { "entityIid": "a", "layerIid": "b", "levelIid": "c", "worldIid": "d" },
+                    "worldX": 0, "worldY": 0, "widPx": 8, "heiPx": 8,
+                    "fields": { "loot": ["Potion", "Sword"] }
+                }]
+            }]);
+        });
+        DesignMap::load(&path)
+    }
+
+    #[test]
+    fn test_enum_defs() {
+        let world = load_project();
+
+        let items = world.enum_def("ItemKind").unwrap();
+        assert_eq!(items.uid(), 30);
+        assert!(!items.is_external());
+        assert_eq!(items.tags(), &["loot".to_string()]);
+        let sword = items.value("Sword").unwrap();
+        assert_eq!(sword.color(), "#BE622F");
+        assert_eq!(sword.tile().unwrap().xy_px(), (8, 0));
+        assert_eq!(items.value("Potion").unwrap().color(), "#0000FF");
+
+        let biome = world.enum_def("Biome").unwrap();
+        assert!(biome.is_external());
+        assert_eq!(biome.external_rel_path(), Some("biomes.cdb"));
+
+        assert!(items.check_variants(&["Sword", "Potion"]).is_ok());
+        let error = items.check_variants(&["Sword", "Shield"]).unwrap_err();
+        assert!(
+            error.contains("missing in the Rust enum: Potion"),
+            "{}",
+            error
+        );
+        assert!(error.contains("missing in LDtk: Shield"), "{}", error);
+    }
+
+    #[test]
+    fn test_enum_fields_resolve() {
+        let world = load_project();
+
+        let monster = &world.toc()[0].instances()[0];
+        let loot = monster.field("loot").unwrap().as_array().unwrap();
+        let FieldValue::Enum(potion) = &loot[0] else {
+            panic!("expected an enum value, got {:?}", loot[0]);
+        };
+        assert_eq!(potion.enum_identifier(), "ItemKind");
+        assert_eq!(potion.parse::<ItemKind>(), Ok(ItemKind::Potion));
+        assert_eq!(
+            loot[1].as_enum().unwrap().parse::<ItemKind>(),
+            Ok(ItemKind::Sword)
+        );
+        assert_eq!(world.resolve_enum(potion).unwrap().color(), "#0000FF");
+    }
+}
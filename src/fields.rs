@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{enums::EnumValue, ldtk_structs::Defs};
+
+/// The value of a field set on an entity or a level in LDtk
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    /// A field left empty
+    Null,
+    /// An `Int` field
+    Int(i64),
+    /// A `Float` field
+    Float(f64),
+    /// A `Bool` field
+    Bool(bool),
+    /// A `String`, `Multilines`, `Color` or `FilePath` field
+    String(String),
+    /// A field typed with an enum of the project or an external enum
+    Enum(EnumValue),
+    /// A `Point` field as the x and y of a grid cell
+    Point(i64, i64),
+    /// An `EntityRef` field
+    EntityRef(EntityRef),
+    /// A `Tile` field
+    Tile(TileRect),
+    /// Any `Array<...>` field
+    Array(Vec<FieldValue>),
+}
+
+/// A reference to an entity instance, possibly in another level
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityRef {
+    entity_iid: String,
+    layer_iid: String,
+    level_iid: String,
+    world_iid: String,
+}
+
+/// A rectangle of pixels picked in a tileset
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileRect {
+    tileset_uid: usize,
+    xy_px: (usize, usize),
+    size_px: (usize, usize),
+}
+
+impl FieldValue {
+    /// Converts the `__value` of a field, `field_type` being the `__type` LDtk gave the field
+    /// if known. Without a type the kind of value is guessed from its json shape.
+    pub(crate) fn from_json(value: &Value, field_type: Option<&str>) -> Self {
+        if let Some(inner) = field_type
+            .and_then(|t| t.strip_prefix("Array<"))
+            .and_then(|t| t.strip_suffix('>'))
+        {
+            return match value {
+                Value::Array(values) => FieldValue::Array(
+                    values
+                        .iter()
+                        .map(|value| FieldValue::from_json(value, Some(inner)))
+                        .collect(),
+                ),
+                _ => FieldValue::Null,
+            };
+        }
+
+        if let (Some(enum_identifier), Value::String(s)) =
+            (field_type.and_then(enum_identifier), value)
+        {
+            return FieldValue::Enum(EnumValue::new(enum_identifier, s));
+        }
+
+        match (field_type, value) {
+            (_, Value::Null) => FieldValue::Null,
+            (Some("Float"), Value::Number(n)) => FieldValue::Float(n.as_f64().unwrap_or_default()),
+            (_, Value::Number(n)) => match n.as_i64() {
+                Some(n) => FieldValue::Int(n),
+                None => FieldValue::Float(n.as_f64().unwrap_or_default()),
+            },
+            (_, Value::Bool(b)) => FieldValue::Bool(*b),
+            (_, Value::String(s)) => FieldValue::String(s.clone()),
+            (_, Value::Array(values)) => FieldValue::Array(
+                values
+                    .iter()
+                    .map(|value| FieldValue::from_json(value, None))
+                    .collect(),
+            ),
+            (_, Value::Object(_)) => object_value(value),
+        }
+    }
+
+    /// The integer, for `Int` fields
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            FieldValue::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The number, for `Float` and `Int` fields
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            FieldValue::Float(n) => Some(*n),
+            FieldValue::Int(n) => Some(*n as f64),
+            _ => None,
+        }
+    }
+
+    /// The boolean, for `Bool` fields
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            FieldValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The text, for fields saved as strings
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FieldValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The enum value, for enum fields
+    pub fn as_enum(&self) -> Option<&EnumValue> {
+        match self {
+            FieldValue::Enum(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The grid cell, for `Point` fields
+    pub fn as_point(&self) -> Option<(i64, i64)> {
+        match self {
+            FieldValue::Point(x, y) => Some((*x, *y)),
+            _ => None,
+        }
+    }
+
+    /// The values, for `Array<...>` fields
+    pub fn as_array(&self) -> Option<&[FieldValue]> {
+        match self {
+            FieldValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Whether the field was left empty
+    pub fn is_null(&self) -> bool {
+        matches!(self, FieldValue::Null)
+    }
+}
+
+impl EntityRef {
+    /// The unique id of the referenced entity
+    pub fn entity_iid(&self) -> &str {
+        self.entity_iid.as_ref()
+    }
+
+    /// The unique id of the layer holding the referenced entity
+    pub fn layer_iid(&self) -> &str {
+        self.layer_iid.as_ref()
+    }
+
+    /// The unique id of the level holding the referenced entity
+    pub fn level_iid(&self) -> &str {
+        self.level_iid.as_ref()
+    }
+
+    /// The unique id of the world holding the referenced entity
+    pub fn world_iid(&self) -> &str {
+        self.world_iid.as_ref()
+    }
+}
+
+impl TileRect {
+    /// Reads a tile rectangle LDtk saves as `{ tilesetUid, x, y, w, h }`
+    pub(crate) fn from_json(value: &Value) -> Option<Self> {
+        let size = |key: &str| value[key].as_u64().unwrap_or_default() as usize;
+        Some(Self {
+            tileset_uid: value["tilesetUid"].as_u64()? as usize,
+            xy_px: (size("x"), size("y")),
+            size_px: (size("w"), size("h")),
+        })
+    }
+
+    /// The uid of the tileset the tile is picked from
+    pub fn tileset_uid(&self) -> usize {
+        self.tileset_uid
+    }
+
+    /// The x and y of the top left corner of the rectangle in the tileset, in pixels
+    pub fn xy_px(&self) -> (usize, usize) {
+        self.xy_px
+    }
+
+    /// The width and height of the rectangle, in pixels
+    pub fn size_px(&self) -> (usize, usize) {
+        self.size_px
+    }
+}
+
+/// Reads the values LDtk saves as objects: points, entity references and tiles
+fn object_value(value: &Value) -> FieldValue {
+    let string = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+    let number = |key: &str| value[key].as_i64().unwrap_or_default();
+
+    if value.get("cx").is_some() {
+        FieldValue::Point(number("cx"), number("cy"))
+    } else if value.get("entityIid").is_some() {
+        FieldValue::EntityRef(EntityRef {
+            entity_iid: string("entityIid"),
+            layer_iid: string("layerIid"),
+            level_iid: string("levelIid"),
+            world_iid: string("worldIid"),
+        })
+    } else if let Some(tile) = TileRect::from_json(value) {
+        FieldValue::Tile(tile)
+    } else {
+        FieldValue::Null
+    }
+}
+
+/// The enum of fields typed `LocalEnum.Name` or `ExternEnum.Name`
+fn enum_identifier(field_type: &str) -> Option<&str> {
+    field_type
+        .strip_prefix("LocalEnum.")
+        .or_else(|| field_type.strip_prefix("ExternEnum."))
+}
+
+/// Converts the `fieldInstances` of an entity or a level, by their identifier
+pub(crate) fn field_instances<'a>(
+    fields: impl Iterator<Item = &'a Value>,
+) -> HashMap<String, FieldValue> {
+    fields
+        .filter_map(|field| {
+            Some((
+                field["__identifier"].as_str()?.to_string(),
+                FieldValue::from_json(&field["__value"], field["__type"].as_str()),
+            ))
+        })
+        .collect()
+}
+
+/// The `__type` of every field defined on an entity, by entity identifier then field identifier
+pub(crate) fn entity_field_types(defs: &Defs) -> HashMap<String, HashMap<String, String>> {
+    defs.entities
+        .iter()
+        .flatten()
+        .filter_map(|entity| {
+            let field_types = entity
+                .field_defs
+                .iter()
+                .flatten()
+                .flatten()
+                .filter_map(|field| {
+                    Some((
+                        field["identifier"].as_str()?.to_string(),
+                        field["__type"].as_str()?.to_string(),
+                    ))
+                })
+                .collect();
+            Some((entity.identifier.clone()?, field_types))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::FieldValue;
+
+    #[test]
+    fn test_typed_values() {
+        assert_eq!(
+            FieldValue::from_json(&json!(3), Some("Float")),
+            FieldValue::Float(3.0)
+        );
+        assert_eq!(FieldValue::from_json(&json!(3), None).as_int(), Some(3));
+        assert_eq!(
+            FieldValue::from_json(&json!({ "cx": 2, "cy": 5 }), Some("Point")).as_point(),
+            Some((2, 5))
+        );
+        let array = FieldValue::from_json(&json!([1, null]), Some("Array<Float>"));
+        assert_eq!(
+            array.as_array().unwrap(),
+            &[FieldValue::Float(1.0), FieldValue::Null]
+        );
+
+        let entity_ref =
+            json!({ "entityIid": "a", "layerIid": "b", "levelIid": "c", "worldIid": "d" });
+        match FieldValue::from_json(&entity_ref, Some("EntityRef")) {
+            FieldValue::EntityRef(entity_ref) => assert_eq!(entity_ref.level_iid(), "c"),
+            other => panic!("expected an entity ref, got {:?}", other),
+        }
+        let tile = json!({ "tilesetUid": 1, "x": 8, "y": 16, "w": 8, "h": 8 });
+        match FieldValue::from_json(&tile, Some("Tile")) {
+            FieldValue::Tile(tile) => assert_eq!(tile.xy_px(), (8, 16)),
+            other => panic!("expected a tile, got {:?}", other),
+        }
+    }
+}
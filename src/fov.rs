@@ -0,0 +1,228 @@
+use crate::design_map::{DesignLevel, TileContents};
+
+/// Transforms from the first octant into each of the eight, as (xx, xy, yx, yy)
+const OCTANTS: [(i64, i64, i64, i64); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+impl DesignLevel {
+    /// The tiles visible from `origin` within `radius` tiles, using recursive shadowcasting.
+    ///
+    /// `opaque` decides which tiles block the view, e.g. `|tile| tile.value() == wall`.
+    /// Opaque tiles are visible themselves, so walls around a room are lit.
+    /// The origin is always visible, an origin outside the level sees nothing.
+    pub fn fov(
+        &self,
+        origin: (usize, usize),
+        radius: usize,
+        opaque: impl Fn(&TileContents) -> bool,
+    ) -> Vec<(usize, usize)> {
+        if !self.in_bounds(origin.0, origin.1) {
+            return vec![];
+        }
+
+        let mut visible = vec![false; self.level.len()];
+        visible[origin.1 * self.width + origin.0] = true;
+        for octant in OCTANTS {
+            self.cast_light(
+                &mut visible,
+                &opaque,
+                origin,
+                radius as i64,
+                1,
+                1.0,
+                0.0,
+                octant,
+            );
+        }
+
+        visible
+            .iter()
+            .enumerate()
+            .filter(|(_, visible)| **visible)
+            .map(|(idx, _)| (idx % self.width, idx / self.width))
+            .collect()
+    }
+
+    /// Scans one octant row by row from `row`, between the `start` and `end` slopes
+    #[allow(clippy::too_many_arguments)]
+    fn cast_light(
+        &self,
+        visible: &mut [bool],
+        opaque: &impl Fn(&TileContents) -> bool,
+        origin: (usize, usize),
+        radius: i64,
+        row: i64,
+        mut start: f64,
+        end: f64,
+        (xx, xy, yx, yy): (i64, i64, i64, i64),
+    ) {
+        if start < end {
+            return;
+        }
+        let tile = |dx: i64, dy: i64| {
+            let x = usize::try_from(origin.0 as i64 + dx * xx + dy * xy).ok()?;
+            let y = usize::try_from(origin.1 as i64 + dx * yx + dy * yy).ok()?;
+            self.in_bounds(x, y).then_some(y * self.width + x)
+        };
+
+        for distance in row..=radius {
+            let dy = -distance;
+            let mut blocked = false;
+            let mut next_start = start;
+            for dx in -distance..=0 {
+                let left_slope = (dx as f64 - 0.5) / (dy as f64 + 0.5);
+                let right_slope = (dx as f64 + 0.5) / (dy as f64 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+
+                let idx = tile(dx, dy);
+                if let Some(idx) = idx {
+                    if dx * dx + dy * dy <= radius * radius {
+                        visible[idx] = true;
+                    }
+                }
+                let is_opaque = match idx {
+                    Some(idx) => opaque(&self.level[idx]),
+                    // the view stops at the edge of the level
+                    None => true,
+                };
+                if blocked {
+                    if is_opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if is_opaque && distance < radius {
+                    blocked = true;
+                    self.cast_light(
+                        visible,
+                        opaque,
+                        origin,
+                        radius,
+                        distance + 1,
+                        start,
+                        left_slope,
+                        (xx, xy, yx, yy),
+                    );
+                    next_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+
+    /// Whether `b` can be seen from `a` along a Bresenham line.
+    ///
+    /// Only the tiles between the two ends are checked with `opaque`, so a wall can be seen
+    /// but not seen through. Ends outside the level are never in sight.
+    pub fn line_of_sight(
+        &self,
+        a: (usize, usize),
+        b: (usize, usize),
+        opaque: impl Fn(&TileContents) -> bool,
+    ) -> bool {
+        if !self.in_bounds(a.0, a.1) || !self.in_bounds(b.0, b.1) {
+            return false;
+        }
+
+        let (mut x, mut y) = (a.0 as i64, a.1 as i64);
+        let (x1, y1) = (b.0 as i64, b.1 as i64);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            if (x, y) == (x1, y1) {
+                return true;
+            }
+            if (x, y) != (a.0 as i64, a.1 as i64)
+                && opaque(&self.level[y as usize * self.width + x as usize])
+            {
+                return false;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{design_map::TileContents, test_support::ascii_level};
+
+    #[test]
+    fn test_fov_open_room() {
+        let level = ascii_level(&[
+            ".....", //
+            ".....", //
+            ".....", //
+            ".....", //
+            ".....", //
+        ]);
+        let visible = level.fov((2, 2), 10, |tile| tile.value() == 1);
+        assert_eq!(visible.len(), 25);
+
+        let visible = level.fov((2, 2), 1, |tile| tile.value() == 1);
+        assert_eq!(visible.len(), 5);
+        assert!(!visible.contains(&(1, 1)));
+        assert!(level.fov((5, 5), 3, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_fov_behind_wall() {
+        let level = ascii_level(&[
+            ".....", //
+            "..#..", //
+            ".....", //
+            ".....", //
+        ]);
+        let visible = level.fov((2, 3), 10, |tile| tile.value() == 1);
+
+        assert!(visible.contains(&(2, 3)));
+        assert!(visible.contains(&(2, 1)));
+        assert!(!visible.contains(&(2, 0)));
+        assert!(visible.contains(&(0, 0)));
+        assert!(visible.contains(&(4, 0)));
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let level = ascii_level(&[
+            "......", //
+            "..#...", //
+            "......", //
+        ]);
+        let wall = |tile: &TileContents| tile.value() == 1;
+
+        assert!(level.line_of_sight((0, 0), (5, 0), wall));
+        assert!(level.line_of_sight((0, 1), (2, 1), wall));
+        assert!(!level.line_of_sight((0, 1), (5, 1), wall));
+        assert!(!level.line_of_sight((1, 0), (3, 2), wall));
+        assert!(level.line_of_sight((3, 2), (3, 2), wall));
+        assert!(!level.line_of_sight((0, 0), (6, 0), wall));
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+use serde_json::{value::RawValue, Value};
+
+use crate::{
+    design_map::{design_level, tilesets, DesignLevel, DesignTileset},
+    enums::{enum_defs, EnumDef},
+    ldtk_helpers::{read_file, try_get_raw_level},
+    ldtk_structs::{Defs, Header, Level},
+    migration::{check_version, migrate_level, migrate_toc, Version},
+    toc::{toc_entries, TocEntry},
+};
+
+/// The parts of a project read up front, the levels are kept as unparsed json
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LazyProject {
+    json_version: Option<String>,
+    #[serde(rename = "__header__")]
+    header: Option<Header>,
+    defs: Defs,
+    toc: Option<Vec<Option<Value>>>,
+    levels: Vec<Box<RawValue>>,
+}
+
+/// The parts of a level read up front
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLevelHeader {
+    identifier: String,
+    iid: Option<String>,
+    world_x: i64,
+    world_y: i64,
+    world_depth: Option<i64>,
+    px_wid: Option<i64>,
+    px_hei: Option<i64>,
+    external_rel_path: Option<String>,
+}
+
+/// What is known about a level of a `LazyDesignMap` before it is loaded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LevelHeader {
+    name: String,
+    iid: Option<String>,
+    world_px: (i64, i64),
+    world_depth: i64,
+    size_px: (usize, usize),
+}
+
+impl LevelHeader {
+    /// The name of the level
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// The unique id LDtk gave the level
+    pub fn iid(&self) -> Option<&str> {
+        self.iid.as_deref()
+    }
+
+    /// The x and y coordinates of the top left corner of the level in the world, in pixels
+    pub fn world_px(&self) -> (i64, i64) {
+        self.world_px
+    }
+
+    /// The depth of the level in the world, levels with a higher depth are drawn over the others
+    pub fn world_depth(&self) -> i64 {
+        self.world_depth
+    }
+
+    /// The width and height of the level in pixels
+    pub fn size_px(&self) -> (usize, usize) {
+        self.size_px
+    }
+}
+
+/// Where the contents of a level are read from once it is requested
+#[derive(Debug)]
+enum LevelSource {
+    Embedded(Box<RawValue>),
+    External(PathBuf),
+}
+
+#[derive(Debug)]
+struct LazyLevel {
+    header: LevelHeader,
+    source: LevelSource,
+    loaded: OnceLock<DesignLevel>,
+}
+
+/// A DesignMap that only reads the definitions and level headers of a project up front,
+/// converting each level the first time it is requested.
+///
+/// Startup cost then scales with the levels actually visited. Memory does too for projects
+/// saving their levels to separate files in LDtk, levels embedded in the project are kept
+/// as unparsed json until they are loaded.
+pub struct LazyDesignMap {
+    project_path: String,
+    version: Version,
+    defs: Defs,
+    tilesets: HashMap<usize, DesignTileset>,
+    toc: Vec<TocEntry>,
+    enums: HashMap<String, EnumDef>,
+    levels: Vec<LazyLevel>,
+    by_name: HashMap<String, usize>,
+}
+
+impl fmt::Debug for LazyDesignMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the raw definitions are left out, everything useful in them is converted already
+        f.debug_struct("LazyDesignMap")
+            .field("project_path", &self.project_path)
+            .field("version", &self.version)
+            .field("tilesets", &self.tilesets)
+            .field("toc", &self.toc)
+            .field("enums", &self.enums)
+            .field("levels", &self.levels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LazyDesignMap {
+    /// Reads the definitions and level headers of the ldtk file located at path
+    pub fn load(path: impl ToString) -> Self {
+        let project_path = path.to_string();
+        let contents = match read_file(Path::new(&project_path)) {
+            Ok(contents) => contents,
+            Err(e) => panic!("{}", e),
+        };
+        let project: LazyProject = match serde_json::from_str(&contents) {
+            Ok(project) => project,
+            Err(e) => panic!("Could not load level at {} | Error: {}", project_path, e),
+        };
+
+        let version = project
+            .json_version
+            .as_deref()
+            .or_else(|| project.header.as_ref()?.app_version.as_deref());
+        let version = match check_version(version, &project_path) {
+            Ok(version) => version,
+            Err(e) => panic!("{}", e),
+        };
+
+        let project_dir = Path::new(&project_path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let mut levels = vec![];
+        let mut by_name = HashMap::new();
+        for raw_level in project.levels {
+            let header: RawLevelHeader = match serde_json::from_str(raw_level.get()) {
+                Ok(header) => header,
+                Err(e) => panic!(
+                    "Could not read a level header in {} | Error: {}",
+                    project_path, e
+                ),
+            };
+            if by_name
+                .insert(header.identifier.clone(), levels.len())
+                .is_some()
+            {
+                panic!("{} level already existed, will be overwritten and is undesired behavior. Please consult the ldtk file.", header.identifier)
+            }
+
+            let source = match &header.external_rel_path {
+                Some(rel_path) => LevelSource::External(project_dir.join(rel_path)),
+                None => LevelSource::Embedded(raw_level),
+            };
+            let size = |value: Option<i64>| value.unwrap_or_default().max(0) as usize;
+            levels.push(LazyLevel {
+                header: LevelHeader {
+                    name: header.identifier,
+                    iid: header.iid,
+                    world_px: (header.world_x, header.world_y),
+                    world_depth: header.world_depth.unwrap_or_default(),
+                    size_px: (size(header.px_wid), size(header.px_hei)),
+                },
+                source,
+                loaded: OnceLock::new(),
+            });
+        }
+
+        let mut toc = project.toc.unwrap_or_default();
+        migrate_toc(&mut toc, &[], version);
+
+        Self {
+            project_path,
+            version,
+            tilesets: tilesets(&project.defs),
+            toc: toc_entries(&toc, &project.defs),
+            enums: enum_defs(&project.defs),
+            defs: project.defs,
+            levels,
+            by_name,
+        }
+    }
+
+    /// The headers of every level in the order of the ldtk file, none of them are loaded by this
+    pub fn headers(&self) -> impl Iterator<Item = &LevelHeader> {
+        self.levels.iter().map(|level| &level.header)
+    }
+
+    /// The header of the level named `name`
+    pub fn header(&self, name: &str) -> Option<&LevelHeader> {
+        self.by_name.get(name).map(|idx| &self.levels[*idx].header)
+    }
+
+    /// The tilesets of the world by their uid
+    pub fn tilesets(&self) -> &HashMap<usize, DesignTileset> {
+        &self.tilesets
+    }
+
+    /// The entities listed in the table of contents of the project, read without loading any level.
+    ///
+    /// Projects saved before LDtk 1.5 only list the iids of the instances, their positions,
+    /// sizes and fields are then left empty. `DesignMap::toc` has them as it loads every level.
+    pub fn toc(&self) -> &[TocEntry] {
+        &self.toc
+    }
+
+    /// The enums of the project and the external enums it imports, by their identifier
+    pub fn enums(&self) -> &HashMap<String, EnumDef> {
+        &self.enums
+    }
+
+    /// The enum named `identifier`
+    pub fn enum_def(&self, identifier: &str) -> Option<&EnumDef> {
+        self.enums.get(identifier)
+    }
+
+    /// Whether the level named `name` was already requested and converted
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.by_name
+            .get(name)
+            .is_some_and(|idx| self.levels[*idx].loaded.get().is_some())
+    }
+
+    /// The level named `name`, read and converted on the first request
+    pub fn level(&self, name: &str) -> Option<&DesignLevel> {
+        let order = *self.by_name.get(name)?;
+        let lazy_level = &self.levels[order];
+        Some(lazy_level.loaded.get_or_init(|| {
+            let level = self.read_level(&lazy_level.source);
+            design_level(&level, &self.defs, &self.tilesets, order)
+        }))
+    }
+
+    fn read_level(&self, source: &LevelSource) -> Level {
+        let level = match source {
+            LevelSource::Embedded(raw_level) => {
+                let error = |e: serde_json::Error| {
+                    format!(
+                        "Could not load level in {} | Error: {}",
+                        self.project_path, e
+                    )
+                };
+                serde_json::from_str(raw_level.get())
+                    .map_err(error)
+                    .and_then(|mut level| {
+                        migrate_level(&mut level, self.version);
+                        serde_json::from_value(level).map_err(error)
+                    })
+            }
+            LevelSource::External(path) => try_get_raw_level(path, self.version),
+        };
+        match level {
+            Ok(level) => level,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::LazyDesignMap;
+    use crate::{
+        design_map::DesignMap,
+        project_builder::LevelBuilder,
+        test_support::{forest_project, patched, TempPath},
+    };
+
+    fn save_project(dir: &TempPath, external_levels: bool) -> String {
+        let path = dir.join("world.ldtk");
+        forest_project(8, &["wall"])
+            .external_levels(external_levels)
+            .level(LevelBuilder::new("Level_0", (16, 16)).value("Values", (1, 1), 1))
+            .level(
+                LevelBuilder::new("Level_1", (24, 8))
+                    .world_position(16, 0)
+                    .world_depth(2),
+            )
+            .save(&path);
+        path
+    }
+
+    #[test]
+    fn test_headers_without_loading() {
+        let dir = TempPath::dir("lazy_embedded");
+        let world = LazyDesignMap::load(save_project(&dir, false));
+
+        let names: Vec<_> = world.headers().map(|header| header.name()).collect();
+        assert_eq!(names, vec!["Level_0", "Level_1"]);
+        let header = world.header("Level_1").unwrap();
+        assert_eq!(header.world_px(), (16, 0));
+        assert_eq!(header.world_depth(), 2);
+        assert_eq!(header.size_px(), (24, 8));
+        assert!(header.iid().is_some());
+        assert!(!world.is_loaded("Level_0"));
+        assert!(!world.is_loaded("Level_1"));
+
+        let level = world.level("Level_0").unwrap();
+        assert_eq!(level.get(1, 1).unwrap().value(), 1);
+        assert!(world.is_loaded("Level_0"));
+        assert!(!world.is_loaded("Level_1"));
+        assert!(world.level("Level_2").is_none());
+    }
+
+    #[test]
+    fn test_external_levels() {
+        let dir = TempPath::dir("lazy_external");
+        let path = save_project(&dir, true);
+        let world = LazyDesignMap::load(&path);
+
+        assert_eq!(world.headers().count(), 2);
+        assert_eq!(
+            world.level("Level_0").unwrap().get(1, 1).unwrap().value(),
+            1
+        );
+        assert_eq!(world.level("Level_1").unwrap().width(), 3);
+        assert_eq!(world.level("Level_1").unwrap().world_depth(), 2);
+    }
+
+    #[test]
+    fn test_toc_and_enums() {
+        let toc = json!([{
+            "identifier": "Monster1",
+            "instances": [{ "entityIid": "a", "layerIid": "b", "levelIid": "c", "worldIid": "d" }],
+            "instancesData": [{
+                "iids": { "entityIid": "a", "layerIid": "b", "levelIid": "c", "worldIid": "d" },
+                "worldX": 16, "worldY": 8, "widPx": 8, "heiPx": 8, "fields": {}
+            }]
+        }]);
+        let path = patched(
+            "./tests/testmaps/entities.ldtk",
+            "lazy_toc.ldtk",
+            |project| {
+                project["jsonVersion"] = json!("1.5.3");
+                project["toc"] = toc.clone();
+                project["defs"]["enums"] = json!([{
+                    "identifier": "ItemKind",
+                    "uid": 30,
+                    "tags": [],
+                    "values": [{ "id": "Sword", "color": 0, "tileRect": null }]
+                }]);
+            },
+        );
+        let world = LazyDesignMap::load(&path);
+        let loaded = DesignMap::load(&path);
+
+        assert_eq!(world.toc(), loaded.toc());
+        assert_eq!(world.toc()[0].instances()[0].world_px(), (16, 8));
+        assert_eq!(world.enums(), loaded.enums());
+        assert!(world.enum_def("ItemKind").is_some());
+        assert!(format!("{:?}", world).starts_with("LazyDesignMap {"));
+        assert!(!world.is_loaded("Level_0"));
+
+        // older projects only list the iids, the rest is in the levels which are not loaded
+        let path = patched(
+            "./tests/testmaps/entities.ldtk",
+            "lazy_old_toc.ldtk",
+            |project| {
+                project["toc"] =
+                    json!([{ "identifier": "Monster1", "instances": toc[0]["instances"] }]);
+            },
+        );
+        let world = LazyDesignMap::load(&path);
+        let instance = &world.toc()[0].instances()[0];
+        assert_eq!(instance.entity_iid(), "a");
+        assert_eq!(instance.world_px(), (0, 0));
+    }
+}
@@ -1,18 +1,189 @@
-use std::fs;
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::ldtk_structs::LDtk;
+use serde::Serialize;
+
+use serde_json::Value;
+
+use crate::{
+    compression::decompress,
+    ldtk_structs::{LDtk, Level},
+    migration::{migrate_level, migrate_project, migrate_toc, project_version, Version},
+};
 
 /// Deserializes the raw LDtk file into the LDtk struct
 pub(crate) fn get_raw_world(level_path: String) -> LDtk {
-    let contents = match fs::read_to_string(&level_path) {
-        Ok(contents) => contents,
-        Err(e) => panic!("Should have been able to read the file: {} | Error: {} | Please report this error on Github so I can fix the game for you and everyone else :)", &level_path, e) 
+    match try_get_raw_world(&level_path) {
+        Ok(world) => world,
+        Err(e) => panic!("{} | Please report this error on Github so I can fix the game for you and everyone else :)", e)
+    }
+}
+
+/// Deserializes the raw LDtk file into the LDtk struct, returning what went wrong instead of panicking
+pub(crate) fn try_get_raw_world(level_path: &str) -> Result<LDtk, String> {
+    let contents = read_file(Path::new(level_path))?;
+    let project_dir = Path::new(level_path).parent().unwrap_or(Path::new(""));
+    try_parse_raw_world(&contents, level_path, |rel_path| {
+        read_file(&project_dir.join(rel_path))
+    })
+}
+
+/// Deserializes the contents of a LDtk file, `level_path` only being used in errors.
+///
+/// Levels saved in their own files are read with `read_level`, given their path relative to the project.
+pub(crate) fn try_parse_raw_world(
+    contents: &str,
+    level_path: &str,
+    mut read_level: impl FnMut(&str) -> Result<String, String>,
+) -> Result<LDtk, String> {
+    let mut project: Value = match serde_json::from_str(contents) {
+        Ok(project) => project,
+        Err(e) => {
+            return Err(format!(
+                "Could not load level at {} | Error: {}",
+                level_path, e
+            ))
+        }
     };
-    let ldtk_world: LDtk = match serde_json::from_str(&contents) {
+    let version = project_version(&project, level_path)?;
+    migrate_project(&mut project, version);
+    let mut world: LDtk = match serde_json::from_value(project) {
         Ok(world) => world,
-        Err(e) => panic!("Could not load level at {} | Error: {} | Please report this error on Github so I can fix the game for you and everyone else :)", level_path, e)
+        Err(e) => {
+            return Err(format!(
+                "Could not load level at {} | Error: {}",
+                level_path, e
+            ))
+        }
+    };
+
+    // levels saved in their own files only keep their header in the project
+    for level in world.levels.iter_mut() {
+        if level.layer_instances.is_none() {
+            if let Some(rel_path) = level.external_rel_path.as_ref().and_then(|p| p.as_str()) {
+                let external = try_parse_raw_level(&read_level(rel_path)?, rel_path, version)?;
+                level.layer_instances = external.layer_instances;
+                level.field_instances = external.field_instances;
+            }
+        }
+    }
+    if let Some(toc) = world.toc.as_mut() {
+        migrate_toc(toc, &world.levels, version);
+    }
+    Ok(world)
+}
+
+/// Deserializes a level saved in its own .ldtkl file by LDtk `version`
+pub(crate) fn try_get_raw_level(level_path: &Path, version: Version) -> Result<Level, String> {
+    let contents = read_file(level_path)?;
+    try_parse_raw_level(&contents, &level_path.display().to_string(), version)
+}
+
+/// Deserializes the contents of a .ldtkl file, `level_path` only being used in errors
+fn try_parse_raw_level(
+    contents: &str,
+    level_path: &str,
+    version: Version,
+) -> Result<Level, String> {
+    let error =
+        |e: serde_json::Error| format!("Could not load level at {} | Error: {}", level_path, e);
+    let mut level: Value = serde_json::from_str(contents).map_err(error)?;
+    migrate_level(&mut level, version);
+    serde_json::from_value(level).map_err(error)
+}
+
+/// Reads a ldtk file as text, decompressing it when it is gzip or zstd compressed
+pub(crate) fn read_file(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| {
+        format!(
+            "Should have been able to read the file: {} | Error: {}",
+            path.display(),
+            e
+        )
+    })?;
+    decompress(&bytes, &path.display().to_string())
+}
+
+/// Serializes the LDtk struct back into a raw LDtk file at `level_path`
+pub(crate) fn write_raw_world(ldtk_world: &LDtk, level_path: String) {
+    write_json(ldtk_world, level_path)
+}
+
+/// Saves every level of the project at `project_path` in its own .ldtkl file in a folder
+/// named after the project, like LDtk does, leaving only their headers in `ldtk_world`
+pub(crate) fn write_external_levels(ldtk_world: &mut LDtk, project_path: &str) {
+    let project = Path::new(project_path);
+    let stem = project
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if let Err(e) = fs::create_dir_all(project.with_file_name(&stem)) {
+        panic!(
+            "Should have been able to create the levels folder next to {} | Error: {}",
+            project_path, e
+        )
+    }
+    for level in ldtk_world.levels.iter_mut() {
+        let rel_path = format!("{}/{}.ldtkl", stem, level.identifier);
+        write_json(
+            level,
+            project.with_file_name(&rel_path).display().to_string(),
+        );
+        level.external_rel_path = Some(Value::String(rel_path));
+        level.layer_instances = None;
+    }
+}
+
+/// Serializes any part of a project, e.g. a level saved in its own file, to `level_path`
+pub(crate) fn write_json(value: &impl Serialize, level_path: String) {
+    let contents = match serde_json::to_string_pretty(value) {
+        Ok(contents) => contents,
+        Err(e) => panic!(
+            "Could not serialize the world for {} | Error: {}",
+            level_path, e
+        ),
     };
-    ldtk_world
+    if let Err(e) = fs::write(&level_path, contents) {
+        panic!(
+            "Should have been able to write the file: {} | Error: {}",
+            level_path, e
+        )
+    }
+}
+
+/// Creates a new random iid in the UUID v4 format LDtk uses for its instances
+pub(crate) fn new_iid() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    let mut bytes = [0u8; 16];
+    for half in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(nanos);
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        half.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
 }
 
 /// Converts the gridpx (x, y) into the index in the 1d vec
@@ -21,14 +192,27 @@ pub(crate) fn gridpx_to_idx((x, y): (usize, usize), width: usize) -> usize {
 }
 
 /// Converts the src (x, y) into the index of an atlas sized 16 x 16, 8 pixel square
-const ATLAS_WIDTH: usize = 16;
+pub(crate) const ATLAS_WIDTH: usize = 16;
 pub(crate) fn src_to_atlas_index((x, y): (usize, usize), px_size: usize) -> usize {
     x / px_size + y / px_size * ATLAS_WIDTH
 }
 
+/// Converts the index of an atlas back into the src (x, y), the inverse of `src_to_atlas_index`
+pub(crate) fn atlas_index_to_src(atlas_index: usize, px_size: usize) -> (usize, usize) {
+    (
+        atlas_index % ATLAS_WIDTH * px_size,
+        atlas_index / ATLAS_WIDTH * px_size,
+    )
+}
+
+/// Converts the index of an atlas into the tile id LDtk uses for an atlas `columns` tiles wide
+pub(crate) fn atlas_index_to_tile_id(atlas_index: usize, columns: usize) -> usize {
+    atlas_index % ATLAS_WIDTH + atlas_index / ATLAS_WIDTH * columns
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{get_raw_world, gridpx_to_idx, src_to_atlas_index};
+    use super::{atlas_index_to_src, get_raw_world, gridpx_to_idx, new_iid, src_to_atlas_index};
 
     #[test]
     #[should_panic]
@@ -39,7 +223,7 @@ mod tests {
 
     #[test]
     fn test_gridpx_to_idx() {
-        let px = (0 / 8, 0);
+        let px = (0, 0);
         let px2 = (48 / 8, 0);
 
         let idx = gridpx_to_idx(px, 40);
@@ -60,4 +244,21 @@ mod tests {
         assert_eq!(idx, 4, "idx did not match the expected.");
         assert_eq!(idx2, 6, "idx2 did not match the expected.");
     }
+
+    #[test]
+    fn test_atlas_index_to_src() {
+        assert_eq!(atlas_index_to_src(4, 16), (64, 0));
+        assert_eq!(
+            atlas_index_to_src(src_to_atlas_index((24, 40), 8), 8),
+            (24, 40)
+        );
+    }
+
+    #[test]
+    fn test_new_iid_is_unique() {
+        let iid = new_iid();
+        assert_eq!(iid.len(), 36);
+        assert_eq!(&iid[14..15], "4", "iid is not a version 4 uuid");
+        assert_ne!(iid, new_iid());
+    }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,8 +17,8 @@ pub struct LDtk {
     pub(crate) world_grid_height: Option<i64>,
     pub(crate) default_level_width: Option<i64>,
     pub(crate) default_level_height: Option<i64>,
-    pub(crate) default_pivot_x: Option<i64>,
-    pub(crate) default_pivot_y: Option<i64>,
+    pub(crate) default_pivot_x: Option<f64>,
+    pub(crate) default_pivot_y: Option<f64>,
     pub(crate) default_grid_size: Option<i64>,
     pub(crate) bg_color: Option<String>,
     pub(crate) default_level_bg_color: Option<String>,
@@ -39,6 +40,9 @@ pub struct LDtk {
     pub(crate) levels: Vec<Level>,
     pub(crate) worlds: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) dummy_world_iid: Option<String>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -50,6 +54,9 @@ pub struct Defs {
     pub(crate) enums: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) external_enums: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) level_fields: Option<Vec<Option<serde_json::Value>>>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -69,6 +76,9 @@ pub struct Entity {
     pub(crate) min_height: Option<serde_json::Value>,
     pub(crate) max_height: Option<serde_json::Value>,
     pub(crate) keep_aspect_ratio: Option<bool>,
+    pub(crate) tile_opacity: Option<f64>,
+    pub(crate) fill_opacity: Option<f64>,
+    pub(crate) line_opacity: Option<f64>,
     pub(crate) hollow: Option<bool>,
     pub(crate) color: Option<String>,
     pub(crate) render_mode: Option<String>,
@@ -80,12 +90,15 @@ pub struct Entity {
     pub(crate) max_count: Option<i64>,
     pub(crate) limit_scope: Option<String>,
     pub(crate) limit_behavior: Option<String>,
-    pub(crate) pivot_x: Option<i64>,
-    pub(crate) pivot_y: Option<i64>,
+    pub(crate) pivot_x: Option<f64>,
+    pub(crate) pivot_y: Option<f64>,
     pub(crate) field_defs: Option<Vec<Option<serde_json::Value>>>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tile {
     pub(crate) tileset_uid: Option<i64>,
@@ -109,14 +122,16 @@ pub struct Layer {
     pub(crate) grid_size: Option<i64>,
     pub(crate) guide_grid_wid: Option<i64>,
     pub(crate) guide_grid_hei: Option<i64>,
+    pub(crate) display_opacity: Option<f64>,
+    pub(crate) inactive_opacity: Option<f64>,
     pub(crate) hide_in_list: Option<bool>,
     pub(crate) hide_fields_when_inactive: Option<bool>,
     pub(crate) can_select_when_inactive: Option<bool>,
     pub(crate) render_in_world_view: Option<bool>,
     pub(crate) px_offset_x: Option<i64>,
     pub(crate) px_offset_y: Option<i64>,
-    pub(crate) parallax_factor_x: Option<i64>,
-    pub(crate) parallax_factor_y: Option<i64>,
+    pub(crate) parallax_factor_x: Option<f64>,
+    pub(crate) parallax_factor_y: Option<f64>,
     pub(crate) parallax_scaling: Option<bool>,
     pub(crate) required_tags: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) excluded_tags: Option<Vec<Option<serde_json::Value>>>,
@@ -124,8 +139,11 @@ pub struct Layer {
     pub(crate) auto_rule_groups: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) auto_source_layer_def_uid: Option<serde_json::Value>,
     pub(crate) tileset_def_uid: Option<i64>,
-    pub(crate) tile_pivot_x: Option<i64>,
-    pub(crate) tile_pivot_y: Option<i64>,
+    pub(crate) tile_pivot_x: Option<f64>,
+    pub(crate) tile_pivot_y: Option<f64>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -150,6 +168,9 @@ pub struct Tileset {
     pub(crate) custom_data: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) saved_selections: Option<Vec<Option<serde_json::Value>>>,
     pub(crate) cached_pixel_data: Option<CachedPixelData>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -198,9 +219,13 @@ pub struct Level {
     pub(crate) bg_pos: Option<serde_json::Value>,
     pub(crate) external_rel_path: Option<serde_json::Value>,
     pub(crate) field_instances: Option<Vec<Option<serde_json::Value>>>,
-    pub(crate) layer_instances: Vec<LayerInstance>,
+    /// `None` when the level is saved in its own file, see `external_rel_path`
+    pub(crate) layer_instances: Option<Vec<LayerInstance>>,
     #[serde(rename = "__neighbours")]
     pub(crate) neighbours: Option<Vec<Option<serde_json::Value>>>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -216,6 +241,8 @@ pub struct LayerInstance {
     pub(crate) height: usize,
     #[serde(rename = "__gridSize")]
     pub(crate) grid_size: usize,
+    #[serde(rename = "__opacity")]
+    pub(crate) opacity: Option<f64>,
     #[serde(rename = "__pxTotalOffsetX")]
     pub(crate) px_total_offset_x: Option<i64>,
     #[serde(rename = "__pxTotalOffsetY")]
@@ -237,6 +264,9 @@ pub struct LayerInstance {
     pub(crate) override_tileset_uid: Option<serde_json::Value>,
     pub(crate) grid_tiles: Option<Vec<GridTile>>,
     pub(crate) entity_instances: Option<Vec<EntityInstance>>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -247,7 +277,7 @@ pub struct EntityInstance {
     #[serde(rename = "__grid")]
     pub(crate) grid: Vec<i64>,
     #[serde(rename = "__pivot")]
-    pub(crate) pivot: Option<Vec<i64>>,
+    pub(crate) pivot: Option<Vec<f64>>,
     #[serde(rename = "__tags")]
     pub(crate) tags: Vec<String>,
     #[serde(rename = "__tile")]
@@ -260,6 +290,9 @@ pub struct EntityInstance {
     pub(crate) def_uid: Option<i64>,
     pub(crate) px: Option<Vec<i64>>,
     pub(crate) field_instances: Option<Vec<Option<serde_json::Value>>>,
+    /// Fields of newer LDtk versions not modelled above, kept so saving the project does not drop them
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 impl EntityInstance {
@@ -304,7 +337,7 @@ pub(crate) struct GridTile {
     pub(crate) f: i64,
     pub(crate) t: i64,
     pub(crate) d: Vec<i64>,
-    pub(crate) a: i64,
+    pub(crate) a: f64,
 }
 
 impl GridTile {
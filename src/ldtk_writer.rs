@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use serde_json::Map;
+
+use crate::{
+    design_map::{DesignLevel, DesignMap, ENTITIES, GROUND, VALUES},
+    ldtk_helpers::{
+        atlas_index_to_tile_id, get_raw_world, gridpx_to_idx, new_iid, write_external_levels,
+        write_raw_world,
+    },
+    ldtk_structs::{Entity, EntityInstance, GridTile, LayerInstance, Level},
+};
+
+impl DesignMap {
+    /// Writes the tiles, values and entities of this DesignMap into the ldtk file at `original_project`,
+    /// saving the result as a new ldtk file at `out_path`.
+    ///
+    /// Only the "Ground", "Entities" and "Values" layers of levels with the same name are updated,
+    /// everything else in the project is kept as it was so the result can still be opened in LDtk.
+    /// Projects saving their levels in separate files get them written next to `out_path`,
+    /// in a folder named after it like LDtk does.
+    pub fn save_into(&self, original_project: impl ToString, out_path: impl ToString) {
+        let mut ldtk_world = get_raw_world(original_project.to_string());
+
+        for (level_name, design_level) in self.levels.iter() {
+            let entity_defs = &ldtk_world.defs.entities;
+            let tilesets = &ldtk_world.defs.tilesets;
+            let level = match ldtk_world
+                .levels
+                .iter_mut()
+                .find(|level| level.identifier.eq(level_name))
+            {
+                Some(level) => level,
+                None => panic!(
+                    "{} level does not exist in {}, only existing levels can be saved into a project.",
+                    level_name,
+                    original_project.to_string()
+                ),
+            };
+
+            let tileset_columns = |uid: usize| {
+                tilesets
+                    .iter()
+                    .find(|tileset| tileset.uid == uid)
+                    .and_then(|tileset| match tileset.c_wid {
+                        Some(c_wid) => Some(c_wid),
+                        None => Some(tileset.px_wid? / tileset.tile_grid_size?),
+                    })
+                    .unwrap_or(1)
+            };
+
+            save_level(design_level, level, entity_defs.as_deref(), tileset_columns);
+        }
+
+        let out_path = out_path.to_string();
+        if ldtk_world.external_levels == Some(true) {
+            write_external_levels(&mut ldtk_world, &out_path);
+        }
+        write_raw_world(&ldtk_world, out_path);
+    }
+}
+
+/// Writes the contents of `design_level` into the layer instances of `level`
+fn save_level(
+    design_level: &DesignLevel,
+    level: &mut Level,
+    entity_defs: Option<&[Entity]>,
+    tileset_columns: impl Fn(usize) -> i64,
+) {
+    if let Some(layer) = find_layer(level, GROUND) {
+        let columns = tileset_columns(layer.tileset_def_uid.unwrap_or_default());
+        save_ground(design_level, layer, columns);
+    }
+
+    if let Some(layer) = find_layer(level, ENTITIES) {
+        save_entities(design_level, layer, entity_defs.unwrap_or_default());
+    }
+
+    if let Some(layer) = find_layer(level, VALUES) {
+        if let Some(values) = &mut layer.int_grid_csv {
+            for (idx, val) in values.iter_mut().enumerate() {
+                if let Some(contents) = design_level.level.get(idx) {
+                    *val = contents.value;
+                }
+            }
+        }
+    }
+}
+
+fn find_layer<'a>(level: &'a mut Level, identifier: &str) -> Option<&'a mut LayerInstance> {
+    level
+        .layer_instances
+        .iter_mut()
+        .flatten()
+        .find(|layer| layer.identifier.eq(identifier))
+}
+
+/// Rebuilds the gridTiles of the Ground layer, keeping the original tiles of unchanged cells
+/// so stacked tiles and their opacity survive the round trip
+fn save_ground(design_level: &DesignLevel, layer: &mut LayerInstance, tileset_columns: i64) {
+    let grid_size = layer.grid_size;
+    let mut original_tiles: HashMap<usize, Vec<GridTile>> = HashMap::new();
+    for tile in layer.grid_tiles.take().unwrap_or_default() {
+        let tile_index = gridpx_to_idx(
+            (tile.grid_x() / grid_size, tile.grid_y() / grid_size),
+            layer.width,
+        );
+        original_tiles.entry(tile_index).or_default().push(tile);
+    }
+
+    let mut grid_tiles = vec![];
+    for (idx, contents) in design_level.level.iter().enumerate() {
+        if !contents.has_tile {
+            continue;
+        }
+        let src = design_level
+            .tileset
+            .atlas_index_to_src(contents.atlas_index, grid_size);
+        let f = contents.flip_bits as i64;
+
+        if let Some(tiles) = original_tiles.remove(&idx) {
+            let unchanged = |top: &GridTile| {
+                design_level.tileset.src_to_atlas_index(top.src, grid_size) == contents.atlas_index
+                    && top.f == f
+            };
+            if tiles.last().is_some_and(unchanged) {
+                grid_tiles.extend(tiles);
+                continue;
+            }
+        }
+
+        grid_tiles.push(new_grid_tile(
+            idx,
+            layer.width,
+            grid_size,
+            src,
+            contents.flip_bits,
+            atlas_index_to_tile_id(contents.atlas_index, tileset_columns as usize),
+        ));
+    }
+
+    layer.grid_tiles = Some(grid_tiles);
+}
+
+/// Creates a gridTile placing the sprite at `src` in the atlas on the tile at `idx` of a layer
+pub(crate) fn new_grid_tile(
+    idx: usize,
+    layer_width: usize,
+    grid_size: usize,
+    src: (usize, usize),
+    flip_bits: u8,
+    tile_id: usize,
+) -> GridTile {
+    let (x, y) = (idx % layer_width, idx / layer_width);
+    GridTile {
+        px: (x * grid_size, y * grid_size),
+        src,
+        f: flip_bits as i64,
+        t: tile_id as i64,
+        d: vec![idx as i64],
+        a: 1.0,
+    }
+}
+
+/// Rebuilds the entityInstances of the Entities layer, keeping the original instance when the
+/// same entity is still on the same tile so its iid and fields are not lost.
+///
+/// A DesignLevel only holds the last entity placed on a tile, the ones below it are written back untouched.
+fn save_entities(design_level: &DesignLevel, layer: &mut LayerInstance, entity_defs: &[Entity]) {
+    let mut original_entities: HashMap<usize, Vec<EntityInstance>> = HashMap::new();
+    for entity in layer.entity_instances.take().unwrap_or_default() {
+        let tile_index = gridpx_to_idx((entity.grid_x(), entity.grid_y()), layer.width);
+        original_entities
+            .entry(tile_index)
+            .or_default()
+            .push(entity);
+    }
+
+    let mut entity_instances = vec![];
+    for (idx, contents) in design_level.level.iter().enumerate() {
+        let mut originals = original_entities.remove(&idx).unwrap_or_default();
+        let loaded = originals.pop();
+        entity_instances.extend(originals);
+
+        let Some(entity_name) = &contents.entity_name else {
+            continue;
+        };
+        // Identifiers in LDtk can not contain spaces so this undoes the replacement made when loading
+        let identifier = entity_name.replace(' ', "_");
+
+        match loaded {
+            Some(entity) if entity.identifier == identifier => entity_instances.push(entity),
+            _ => {
+                let Some(def) = entity_defs
+                    .iter()
+                    .find(|def| def.identifier.as_deref() == Some(identifier.as_str()))
+                else {
+                    panic!(
+                        "{} entity in level {} does not have a definition in the project.",
+                        entity_name, design_level.level_name
+                    )
+                };
+                let grid = (idx % layer.width, idx / layer.width);
+                entity_instances.push(new_entity_instance(def, grid, layer.grid_size));
+            }
+        }
+    }
+
+    layer.entity_instances = Some(entity_instances);
+}
+
+/// Creates a fresh instance of the entity `def` at the `grid` position
+pub(crate) fn new_entity_instance(
+    def: &Entity,
+    (x, y): (usize, usize),
+    grid_size: usize,
+) -> EntityInstance {
+    let pivot_x = def.pivot_x.unwrap_or_default();
+    let pivot_y = def.pivot_y.unwrap_or_default();
+    let tags = def
+        .tags
+        .iter()
+        .flatten()
+        .flatten()
+        .filter_map(|tag| tag.as_str().map(|tag| tag.to_string()))
+        .collect();
+
+    EntityInstance {
+        identifier: def.identifier.clone().unwrap_or_default(),
+        grid: vec![x as i64, y as i64],
+        pivot: Some(vec![pivot_x, pivot_y]),
+        tags,
+        tile: def.tile_rect.clone(),
+        smart_color: def.color.clone(),
+        iid: Some(new_iid()),
+        width: def.width,
+        height: def.height,
+        def_uid: def.uid,
+        px: Some(vec![
+            (x * grid_size) as i64 + (pivot_x * grid_size as f64) as i64,
+            (y * grid_size) as i64 + (pivot_y * grid_size as f64) as i64,
+        ]),
+        field_instances: Some(vec![]),
+        extra: Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        design_map::DesignMap,
+        ldtk_helpers::get_raw_world,
+        project_builder::{LevelBuilder, ProjectBuilder},
+        test_support::{forest_project, patched, TempPath},
+    };
+
+    #[test]
+    fn test_save_unchanged_world() {
+        let original = "./tests/testmaps/two_tileatlases.ldtk";
+        let out = TempPath::new("unchanged.ldtk");
+        DesignMap::load(original).save_into(original, &out);
+
+        let raw = get_raw_world(out.to_string());
+        assert_eq!(raw.levels.len(), 2);
+        let saved = DesignMap::load(&out);
+        let level = &saved.levels()["Level_1"];
+        assert_eq!(level.tileset_name(), "SecondTileset");
+        assert!(level.level().iter().all(|tile| tile.has_tile()));
+    }
+
+    #[test]
+    fn test_save_edited_tiles_and_entities() {
+        let original = "./tests/testmaps/entities.ldtk";
+        let out = TempPath::new("edited.ldtk");
+        let mut world = DesignMap::load(original);
+        {
+            let level = world.levels_mut().get_mut("Level_0").unwrap();
+            level.level_mut()[0].set_entity(None);
+            level.level_mut()[5].set_entity(Some("Monster1"));
+            level.level_mut()[2].set_atlas_index(17);
+            level.level_mut()[2].set_flip(true, false);
+        }
+        world.save_into(original, &out);
+
+        let saved = DesignMap::load(&out);
+        let level = &saved.levels()["Level_0"];
+        assert_eq!(level.level()[0].entity_name(), None);
+        assert_eq!(level.level()[3].entity_name(), Some("Monster1"));
+        assert_eq!(level.level()[5].entity_name(), Some("Monster1"));
+        assert_eq!(level.level()[2].atlas_index(), 17);
+        assert!(level.level()[2].has_tile());
+        assert!(level.level()[2].flip_x());
+        assert!(!level.level()[1].has_tile());
+    }
+
+    #[test]
+    #[should_panic]
+    /// Tests that entities without a definition are not written into the project
+    fn test_save_unknown_entity() {
+        let original = "./tests/testmaps/entities.ldtk";
+        let mut world = DesignMap::load(original);
+        world.levels_mut().get_mut("Level_0").unwrap().level_mut()[1]
+            .set_entity(Some("Not A Monster"));
+        world.save_into(original, TempPath::new("unknown_entity.ldtk"));
+    }
+
+    #[test]
+    fn test_save_stacked_entities() {
+        let original = TempPath::new("stacked.ldtk");
+        forest_project(8, &[])
+            .entity("Chest", (8, 8), &[])
+            .level(
+                LevelBuilder::new("Level_0", (16, 16))
+                    .entity("Entities", (1, 0), "Chest")
+                    .entity("Entities", (1, 0), "Monster1")
+                    .entity("Entities", (0, 1), "Chest")
+                    .entity("Entities", (0, 1), "Monster1"),
+            )
+            .save(&original);
+        let identifiers = |path: &TempPath| -> Vec<String> {
+            let raw = get_raw_world(path.to_string());
+            let layer = &raw.levels[0].layer_instances.as_ref().unwrap()[0];
+            layer
+                .entity_instances
+                .iter()
+                .flatten()
+                .map(|entity| entity.identifier.clone())
+                .collect()
+        };
+
+        let out = TempPath::new("stacked_edited.ldtk");
+        let mut world = DesignMap::load(&original);
+        let level = world.levels_mut().get_mut("Level_0").unwrap();
+        level.level_mut()[2].set_entity(None);
+        world.save_into(&original, &out);
+
+        // the chests below the monsters are kept, also where the monster was removed
+        assert_eq!(identifiers(&out), vec!["Chest", "Monster1", "Chest"]);
+    }
+
+    /// Checks that every key of `original` is still in `saved`, in the nested objects and arrays too
+    fn assert_keys_kept(original: &Value, saved: &Value, path: &str) {
+        match (original, saved) {
+            (Value::Object(original), Value::Object(saved)) => {
+                for (key, value) in original {
+                    let path = format!("{}.{}", path, key);
+                    match saved.get(key) {
+                        Some(saved) => assert_keys_kept(value, saved, &path),
+                        None => panic!("{} was dropped", path),
+                    }
+                }
+            }
+            (Value::Array(original), Value::Array(saved)) => {
+                for (idx, (value, saved)) in original.iter().zip(saved).enumerate() {
+                    assert_keys_kept(value, saved, &format!("{}[{}]", path, idx));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_save_keeps_unknown_fields() {
+        let original = "./tests/testmaps/ldtk_1_5.ldtk";
+        let out = TempPath::new("ldtk_1_5.ldtk");
+        DesignMap::load(original).save_into(original, &out);
+
+        let read = |path: &str| -> Value {
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+        let (original, saved) = (read(original), read(&out.to_string()));
+        assert_eq!(
+            original["defs"]["layers"][0]["intGridValuesGroups"],
+            saved["defs"]["layers"][0]["intGridValuesGroups"]
+        );
+        assert_keys_kept(&original, &saved, "project");
+    }
+
+    #[test]
+    fn test_save_tiles_of_wide_atlas() {
+        let built = TempPath::new("wide_built.ldtk");
+        ProjectBuilder::new()
+            .tileset("Wide", "wide.png", (256, 8), 8)
+            .tiles_layer("Ground", 8, "Wide")
+            .level(LevelBuilder::new("Level_0", (16, 8)).tile("Ground", (1, 0), 0))
+            .save(&built);
+        // a sprite of the 21st column over a faded one, in an atlas 32 sprites wide
+        let original = patched(&built.to_string(), "wide.ldtk", |project| {
+            let tiles = &mut project["levels"][0]["layerInstances"][0]["gridTiles"];
+            let mut below = tiles[0].clone();
+            below["src"] = json!([24, 0]);
+            below["t"] = json!(3);
+            below["a"] = json!(0.5);
+            tiles[0]["src"] = json!([160, 0]);
+            tiles[0]["t"] = json!(20);
+            *tiles = json!([below, tiles[0]]);
+        });
+
+        let out = TempPath::new("wide_saved.ldtk");
+        DesignMap::load(&original).save_into(&original, &out);
+
+        let raw = get_raw_world(out.to_string());
+        let tiles = raw.levels[0].layer_instances.as_ref().unwrap()[0]
+            .grid_tiles
+            .as_ref()
+            .unwrap();
+        let kept: Vec<_> = tiles.iter().map(|tile| (tile.src, tile.a)).collect();
+        assert_eq!(kept, vec![((24, 0), 0.5), ((160, 0), 1.0)]);
+    }
+}
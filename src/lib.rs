@@ -4,9 +4,47 @@
 //! The crate implements the structs for the ldtk map but abstracts them behind a DesignMap that contains the minimal amount of data.
 
 #![warn(missing_docs)]
+#[cfg(feature = "bevy")]
+mod bevy_plugin;
+mod chunks;
+pub mod codegen;
+mod collision;
+mod compression;
+mod convert;
 mod design_map;
+mod diff;
+mod embed;
+mod enums;
+mod fields;
+mod fov;
+mod lazy;
 mod ldtk_helpers;
 mod ldtk_structs; // These are kept internal as they are a rather nasty looking
+mod ldtk_writer;
+mod migration;
+mod outline;
+mod pathfinding;
+mod project_builder;
+mod registry;
+#[cfg(feature = "image")]
+mod render;
+#[cfg(test)]
+mod test_support;
+mod tiled;
+#[cfg(feature = "image")]
+mod tileset_image;
+mod toc;
+mod validation;
+mod world;
+
+#[doc(hidden)]
+pub use convert::__field;
+pub use convert::{FromFieldValue, LdtkEntity, LdtkLevelFields};
+pub use diff::{diff, Change, MapDiff, TileSprite};
+pub use embed::bake;
+#[cfg(feature = "derive")]
+pub use ldtk_map_derive::{LdtkEntity, LdtkLevelFields};
+pub use validation::{validate, Diagnostic, Severity};
 
 /// Exports the user facing LDtk structs
 /// Example Usage:
@@ -14,7 +52,53 @@ mod ldtk_structs; // These are kept internal as they are a rather nasty looking
 ///
 /// ```
 pub mod prelude {
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::DesignMapLoader;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::EntityMarker;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::GridCoords;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::IntGridCell;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::LdtkLevel;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::LdtkLevelSpawned;
+    #[cfg(feature = "bevy")]
+    pub use crate::bevy_plugin::LdtkMapPlugin;
+    pub use crate::chunks::Chunk;
+    pub use crate::collision::CollisionRect;
     pub use crate::design_map::DesignLevel;
     pub use crate::design_map::DesignMap;
+    pub use crate::design_map::DesignTileset;
+    pub use crate::design_map::IntGridValue;
+    pub use crate::design_map::LayerTile;
     pub use crate::design_map::TileContents;
+    pub use crate::design_map::TileLayer;
+    pub use crate::enums::EnumDef;
+    pub use crate::enums::EnumValue;
+    pub use crate::enums::EnumValueDef;
+    pub use crate::fields::EntityRef;
+    pub use crate::fields::FieldValue;
+    pub use crate::fields::TileRect;
+    pub use crate::lazy::LazyDesignMap;
+    pub use crate::lazy::LevelHeader;
+    pub use crate::outline::Outline;
+    pub use crate::pathfinding::CornerCutting;
+    pub use crate::pathfinding::GridPath;
+    pub use crate::pathfinding::Movement;
+    pub use crate::pathfinding::PathCosts;
+    pub use crate::project_builder::LevelBuilder;
+    pub use crate::project_builder::ProjectBuilder;
+    pub use crate::registry::EntityRegistry;
+    pub use crate::registry::EntitySpawn;
+    pub use crate::registry::SpawnReport;
+    pub use crate::registry::UnknownEntity;
+    #[cfg(feature = "image")]
+    pub use crate::render::RenderOptions;
+    pub use crate::tiled::TiledFormat;
+    #[cfg(feature = "image")]
+    pub use crate::tileset_image::TilesetImage;
+    pub use crate::toc::TocEntry;
+    pub use crate::toc::TocInstance;
 }
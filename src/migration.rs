@@ -0,0 +1,259 @@
+use std::{cmp::Ordering, fmt};
+
+use serde_json::{json, Map, Value};
+
+use crate::ldtk_structs::Level;
+
+/// The oldest LDtk version whose files are read, only files from LDtk 1.3 and 1.5 are tested
+const OLDEST_SUPPORTED: Version = Version(1, 0, 0);
+/// The newest LDtk minor version whose files can be read, any patch of it works
+const NEWEST_SUPPORTED: Version = Version(1, 5, u32::MAX);
+
+/// An LDtk version as major, minor and patch
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Version(pub(crate) u32, pub(crate) u32, pub(crate) u32);
+
+impl Version {
+    /// Reads versions like "1.3.4", missing parts count as 0
+    fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.').map(|part| {
+            // pre-releases like "1.5.0-beta" only count by their number
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u32>().ok()
+        });
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        Some(Self(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// Reads the version of the project from `jsonVersion`, or the header for files missing it,
+/// failing for versions whose layout is not known
+pub(crate) fn project_version(project: &Value, path: &str) -> Result<Version, String> {
+    let version = project["jsonVersion"]
+        .as_str()
+        .or_else(|| project["__header__"]["appVersion"].as_str());
+    check_version(version, path)
+}
+
+/// Checks the version saved in the project at `path` is one whose layout is known
+pub(crate) fn check_version(version: Option<&str>, path: &str) -> Result<Version, String> {
+    let Some(version) = version else {
+        return Err(format!(
+            "{} does not say which LDtk version saved it, is it an ldtk file?",
+            path
+        ));
+    };
+    let Some(parsed) = Version::parse(version) else {
+        return Err(format!(
+            "{} has an unreadable LDtk version \"{}\"",
+            path, version
+        ));
+    };
+
+    match (
+        parsed.cmp(&OLDEST_SUPPORTED),
+        parsed.cmp(&NEWEST_SUPPORTED),
+    ) {
+        (Ordering::Less, _) | (_, Ordering::Greater) => Err(format!(
+            "{} was saved by LDtk {} but only files from LDtk {}.{} to {}.{} are supported, open and save it in a supported version of LDtk",
+            path,
+            version,
+            OLDEST_SUPPORTED.0,
+            OLDEST_SUPPORTED.1,
+            NEWEST_SUPPORTED.0,
+            NEWEST_SUPPORTED.1
+        )),
+        _ => Ok(parsed),
+    }
+}
+
+/// Fills in the per tile opacity missing from levels saved before LDtk 1.3,
+/// other layout changes of LDtk 1.0 to 1.2 are not migrated
+pub(crate) fn migrate_level(level: &mut Value, version: Version) {
+    // per tile opacity was added in LDtk 1.3, before it every tile was opaque
+    if version < Version(1, 3, 0) {
+        let layers = level["layerInstances"].as_array_mut().into_iter().flatten();
+        for layer in layers {
+            for key in ["gridTiles", "autoLayerTiles"] {
+                let tiles = layer[key].as_array_mut().into_iter().flatten();
+                for tile in tiles.filter_map(|tile| tile.as_object_mut()) {
+                    tile.entry("a").or_insert(json!(1.0));
+                }
+            }
+        }
+    }
+}
+
+/// Runs `migrate_level` on the levels embedded in a project saved by LDtk `version`
+pub(crate) fn migrate_project(project: &mut Value, version: Version) {
+    let levels = project["levels"].as_array_mut().into_iter().flatten();
+    for level in levels {
+        migrate_level(level, version);
+    }
+}
+
+/// Fills the `instancesData` of the table of contents for projects saved before LDtk 1.5,
+/// which only listed the iids of the entities under `instances`.
+///
+/// The positions, sizes and fields of the entities are looked up in `levels`, so this runs
+/// once the levels are loaded as external levels are needed too. Entities missing from `levels`
+/// only get their iids. The old `instances` are kept so the file still opens in the LDtk that saved it.
+pub(crate) fn migrate_toc(toc: &mut [Option<Value>], levels: &[Level], version: Version) {
+    if version >= Version(1, 5, 0) {
+        return;
+    }
+
+    for entry in toc.iter_mut().flatten() {
+        let Some(entry) = entry.as_object_mut() else {
+            continue;
+        };
+        if entry.contains_key("instancesData") {
+            continue;
+        }
+        let instances_data: Vec<Value> = entry
+            .get("instances")
+            .and_then(|instances| instances.as_array())
+            .into_iter()
+            .flatten()
+            .map(|iids| {
+                let mut data = Map::new();
+                data.insert("iids".to_string(), iids.clone());
+                let entity_iid = iids["entityIid"].as_str().unwrap_or_default();
+                if let Some((world_x, world_y, width, height, fields)) =
+                    find_entity(levels, entity_iid)
+                {
+                    data.insert("worldX".to_string(), json!(world_x));
+                    data.insert("worldY".to_string(), json!(world_y));
+                    data.insert("widPx".to_string(), json!(width));
+                    data.insert("heiPx".to_string(), json!(height));
+                    data.insert("fields".to_string(), fields);
+                }
+                Value::Object(data)
+            })
+            .collect();
+        entry.insert("instancesData".to_string(), Value::Array(instances_data));
+    }
+}
+
+/// The world position, size and field values of the entity with `iid`
+fn find_entity(levels: &[Level], iid: &str) -> Option<(i64, i64, i64, i64, Value)> {
+    levels.iter().find_map(|level| {
+        level
+            .layer_instances
+            .iter()
+            .flatten()
+            .filter_map(|layer| layer.entity_instances.as_ref())
+            .flatten()
+            .find(|entity| entity.iid.as_deref() == Some(iid))
+            .map(|entity| {
+                let px = entity.px.clone().unwrap_or_default();
+                let fields: Map<String, Value> = entity
+                    .field_instances
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .filter_map(|field| {
+                        Some((
+                            field["__identifier"].as_str()?.to_string(),
+                            field["__value"].clone(),
+                        ))
+                    })
+                    .collect();
+                (
+                    level.world_x + px.first().copied().unwrap_or_default(),
+                    level.world_y + px.get(1).copied().unwrap_or_default(),
+                    entity.width.unwrap_or_default(),
+                    entity.height.unwrap_or_default(),
+                    Value::Object(fields),
+                )
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{migrate_level, project_version, Version};
+    use crate::test_support::patched;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(Version::parse("1.3.4"), Some(Version(1, 3, 4)));
+        assert_eq!(Version::parse("1.5"), Some(Version(1, 5, 0)));
+        assert_eq!(Version::parse("1.5.0-beta"), Some(Version(1, 5, 0)));
+        assert_eq!(Version::parse("latest"), None);
+    }
+
+    #[test]
+    fn test_supported_versions() {
+        let project = |version: &str| json!({ "jsonVersion": version });
+        assert_eq!(
+            project_version(&project("1.0.0"), "a.ldtk"),
+            Ok(Version(1, 0, 0))
+        );
+        assert_eq!(
+            project_version(&project("1.5.3"), "a.ldtk"),
+            Ok(Version(1, 5, 3))
+        );
+        assert!(project_version(&project("0.9.3"), "a.ldtk").is_err());
+        let newer = project_version(&project("1.6.0"), "a.ldtk").unwrap_err();
+        assert!(newer.contains("LDtk 1.6.0"), "{}", newer);
+        assert!(project_version(&json!({}), "a.ldtk").is_err());
+
+        let header_only = json!({ "__header__": { "appVersion": "1.2.5" } });
+        assert_eq!(
+            project_version(&header_only, "a.ldtk"),
+            Ok(Version(1, 2, 5))
+        );
+    }
+
+    #[test]
+    fn test_tile_opacity_added() {
+        let mut level = json!({
+            "layerInstances": [{ "gridTiles": [{ "px": [0, 0], "src": [0, 0], "f": 0, "t": 0, "d": [0] }] }]
+        });
+        migrate_level(&mut level, Version(1, 2, 5));
+        assert_eq!(level["layerInstances"][0]["gridTiles"][0]["a"], 1.0);
+    }
+
+    #[test]
+    fn test_migrate_old_project() {
+        let mut entity_iid = json!(null);
+        let path = patched(
+            "./tests/testmaps/entities.ldtk",
+            "migration.ldtk",
+            |project| {
+                project["jsonVersion"] = json!("1.1.3");
+                let level_iid = project["levels"][0]["iid"].clone();
+                entity_iid =
+                    project["levels"][0]["layerInstances"][1]["entityInstances"][0]["iid"].clone();
+                for level in project["levels"].as_array_mut().unwrap() {
+                    for layer in level["layerInstances"].as_array_mut().unwrap() {
+                        for tile in layer["gridTiles"].as_array_mut().unwrap() {
+                            tile.as_object_mut().unwrap().remove("a");
+                        }
+                    }
+                }
+                project["toc"] = json!([{
+                    "identifier": "Monster1",
+                    "instances": [{ "entityIid": entity_iid.clone(), "levelIid": level_iid }]
+                }]);
+            },
+        );
+
+        let world = crate::ldtk_helpers::get_raw_world(path.to_string());
+        let toc = world.toc.unwrap()[0].clone().unwrap();
+        assert_eq!(toc["instancesData"][0]["iids"]["entityIid"], entity_iid);
+        assert!(toc["instancesData"][0]["worldX"].is_i64());
+        assert!(toc["instances"].is_array());
+    }
+}
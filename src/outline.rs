@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use crate::design_map::{DesignLevel, TileContents};
+
+/// A point on the tile grid, in tiles
+type Corner = (i64, i64);
+
+/// The closed outline of one connected solid region made by `DesignLevel::outlines`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outline {
+    outer: Vec<(usize, usize)>,
+    holes: Vec<Vec<(usize, usize)>>,
+}
+
+impl Outline {
+    /// The outer boundary in pixels relative to the level, clockwise on screen.
+    /// The last point connects back to the first.
+    pub fn outer(&self) -> &[(usize, usize)] {
+        self.outer.as_ref()
+    }
+
+    /// The boundaries of the empty areas enclosed by the region, counter-clockwise on screen
+    pub fn holes(&self) -> &[Vec<(usize, usize)>] {
+        self.holes.as_ref()
+    }
+}
+
+impl DesignLevel {
+    /// Traces the boundaries of the tiles matching `solid` into closed polygons in pixels,
+    /// e.g. for chain colliders, marching the edges between solid and empty tiles.
+    ///
+    /// Each region of solid tiles sharing edges gets its own outline with its holes.
+    /// Tiles touching only by a corner are separate regions. Points in the middle of
+    /// straight edges are left out so every point is a corner.
+    pub fn outlines(&self, solid: impl Fn(&TileContents) -> bool) -> Vec<Outline> {
+        let solid: Vec<bool> = self.level.iter().map(solid).collect();
+        let mut region = vec![usize::MAX; self.level.len()];
+        let mut outlines = vec![];
+
+        for start in 0..self.level.len() {
+            if !solid[start] || region[start] != usize::MAX {
+                continue;
+            }
+            let cells = self.flood_fill(&solid, &mut region, start, outlines.len());
+
+            let mut outline = Outline {
+                outer: vec![],
+                holes: vec![],
+            };
+            for ring in trace_rings(&self.region_edges(&region, &cells, outlines.len())) {
+                let points = ring
+                    .iter()
+                    .map(|(x, y)| {
+                        (
+                            *x as usize * self.grid_size_px,
+                            *y as usize * self.grid_size_px,
+                        )
+                    })
+                    .collect();
+                if signed_area(&ring) > 0 {
+                    outline.outer = points;
+                } else {
+                    outline.holes.push(points);
+                }
+            }
+            outlines.push(outline);
+        }
+
+        outlines
+    }
+
+    /// Marks every solid tile connected to `start` by an edge with `id`, returning them
+    fn flood_fill(
+        &self,
+        solid: &[bool],
+        region: &mut [usize],
+        start: usize,
+        id: usize,
+    ) -> Vec<usize> {
+        let mut cells = vec![];
+        let mut stack = vec![start];
+        region[start] = id;
+        while let Some(idx) = stack.pop() {
+            cells.push(idx);
+            let (x, y) = (idx % self.width, idx / self.width);
+            for ((nx, ny), _) in self.neighbours4(x, y) {
+                let next = ny * self.width + nx;
+                if solid[next] && region[next] == usize::MAX {
+                    region[next] = id;
+                    stack.push(next);
+                }
+            }
+        }
+        cells
+    }
+
+    /// The edges between the region and everything else, going clockwise around each tile
+    /// so the region is always on the right
+    fn region_edges(&self, region: &[usize], cells: &[usize], id: usize) -> Vec<(Corner, Corner)> {
+        let inside = |x: i64, y: i64| {
+            x >= 0
+                && y >= 0
+                && self.in_bounds(x as usize, y as usize)
+                && region[y as usize * self.width + x as usize] == id
+        };
+
+        let mut edges = vec![];
+        for idx in cells {
+            let (x, y) = ((idx % self.width) as i64, (idx / self.width) as i64);
+            if !inside(x, y - 1) {
+                edges.push(((x, y), (x + 1, y)));
+            }
+            if !inside(x + 1, y) {
+                edges.push(((x + 1, y), (x + 1, y + 1)));
+            }
+            if !inside(x, y + 1) {
+                edges.push(((x + 1, y + 1), (x, y + 1)));
+            }
+            if !inside(x - 1, y) {
+                edges.push(((x, y + 1), (x, y)));
+            }
+        }
+        edges
+    }
+}
+
+/// Links the edges into closed rings, keeping only the corners
+fn trace_rings(edges: &[(Corner, Corner)]) -> Vec<Vec<Corner>> {
+    let mut leaving: HashMap<Corner, Vec<usize>> = HashMap::new();
+    for (idx, (from, _)) in edges.iter().enumerate() {
+        leaving.entry(*from).or_default().push(idx);
+    }
+    let direction = |idx: usize| {
+        let (from, to) = edges[idx];
+        (to.0 - from.0, to.1 - from.1)
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = vec![];
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        let mut ring = vec![];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let (dx, dy) = direction(current);
+            // where a region touches itself by a corner, turning left splits off the enclosed
+            // area as a hole instead of one ring crossing itself
+            let turns = [(dy, -dx), (dx, dy), (-dy, dx)];
+            let next = turns
+                .iter()
+                .find_map(|turn| {
+                    leaving[&edges[current].1]
+                        .iter()
+                        .copied()
+                        .find(|idx| (!used[*idx] || *idx == first) && direction(*idx) == *turn)
+                })
+                .filter(|next| *next != first);
+
+            if direction(current) != next.map_or(direction(first), direction) {
+                ring.push(edges[current].1);
+            }
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+/// Twice the area enclosed by the ring, positive when it goes clockwise on screen
+fn signed_area(ring: &[Corner]) -> i64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|((x0, y0), (x1, y1))| x0 * y1 - x1 * y0)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::ascii_level;
+
+    #[test]
+    fn test_outline_without_collinear_points() {
+        let level = ascii_level(&[
+            "##.", //
+            "###", //
+        ]);
+        let outlines = level.outlines(|tile| tile.value() == 1);
+
+        assert_eq!(outlines.len(), 1);
+        let mut outer = outlines[0].outer().to_vec();
+        outer.sort();
+        assert_eq!(
+            outer,
+            vec![(0, 0), (0, 16), (16, 0), (16, 8), (24, 8), (24, 16)]
+        );
+        assert!(outlines[0].holes().is_empty());
+    }
+
+    #[test]
+    fn test_outline_with_hole() {
+        let level = ascii_level(&[
+            "####", //
+            "#..#", //
+            "####", //
+        ]);
+        let outlines = level.outlines(|tile| tile.value() == 1);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].outer().len(), 4);
+        assert_eq!(outlines[0].holes().len(), 1);
+        let mut hole = outlines[0].holes()[0].clone();
+        hole.sort();
+        assert_eq!(hole, vec![(8, 8), (8, 16), (24, 8), (24, 16)]);
+    }
+
+    #[test]
+    fn test_hole_touching_the_outside_by_a_corner() {
+        let level = ascii_level(&[
+            "##.", //
+            "#.#", //
+            "###", //
+        ]);
+        let outlines = level.outlines(|tile| tile.value() == 1);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].holes().len(), 1);
+        assert_eq!(outlines[0].holes()[0].len(), 4);
+        assert_eq!(outlines[0].outer().len(), 6);
+    }
+
+    #[test]
+    fn test_diagonal_tiles_are_separate() {
+        let level = ascii_level(&[
+            "#.", //
+            ".#", //
+        ]);
+        let outlines = level.outlines(|tile| tile.value() == 1);
+
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|outline| outline.outer().len() == 4));
+    }
+}
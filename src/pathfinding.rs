@@ -0,0 +1,350 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{
+    design_map::{DesignLevel, IntGridValue, TileContents, NEIGHBOURS4, NEIGHBOURS8},
+    ldtk_helpers::gridpx_to_idx,
+};
+
+/// How far a diagonal step goes compared to a straight one
+const DIAGONAL_COST: f32 = std::f32::consts::SQRT_2;
+
+/// The directions a path is allowed to move in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Movement {
+    /// Up, right, down and left only
+    FourWay,
+    /// Also diagonally, following the corner cutting rule
+    EightWay(CornerCutting),
+}
+
+/// When a diagonal step may pass by the corner of a blocked tile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CornerCutting {
+    /// Diagonal steps ignore the tiles they pass by
+    Allow,
+    /// One of the two tiles passed by may be blocked, squeezing past a single corner
+    IfOneOpen,
+    /// Both tiles passed by have to be open, paths go around every corner
+    Never,
+}
+
+/// A path found by `DesignLevel::find_path`
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridPath {
+    cells: Vec<(usize, usize)>,
+    cost: f32,
+}
+
+impl GridPath {
+    /// The tiles of the path from the start to the goal, both included
+    pub fn cells(&self) -> &[(usize, usize)] {
+        self.cells.as_ref()
+    }
+
+    /// The summed cost of every step, diagonal steps cost √2 times the tile they enter
+    pub fn cost(&self) -> f32 {
+        self.cost
+    }
+}
+
+/// Costs of entering tiles by their IntGrid value, for use as the `cost_fn` of `find_path`.
+///
+/// Values are picked by the names they were given in LDtk, e.g. "wall".
+#[derive(Clone, Debug)]
+pub struct PathCosts {
+    int_grid_values: Vec<IntGridValue>,
+    costs: HashMap<usize, Option<f32>>,
+    default_cost: f32,
+}
+
+impl PathCosts {
+    /// Every tile costs 1, the IntGrid values of `level` can then be blocked or given other costs
+    pub fn new(level: &DesignLevel) -> Self {
+        Self {
+            int_grid_values: level.int_grid_values.clone(),
+            costs: HashMap::new(),
+            default_cost: 1.0,
+        }
+    }
+
+    /// The cost of the tiles without a value or with a value that was not given a cost
+    pub fn default_cost(mut self, cost: f32) -> Self {
+        self.default_cost = cost;
+        self
+    }
+
+    /// Tiles with the IntGrid value named `identifier` can not be walked through
+    pub fn block(mut self, identifier: &str) -> Self {
+        let value = self.value(identifier);
+        self.costs.insert(value, None);
+        self
+    }
+
+    /// Tiles with the IntGrid value named `identifier` cost `cost` to enter
+    pub fn cost(mut self, identifier: &str, cost: f32) -> Self {
+        let value = self.value(identifier);
+        self.costs.insert(value, Some(cost));
+        self
+    }
+
+    /// The cost of entering `tile`, `None` when it is blocked
+    pub fn tile_cost(&self, tile: &TileContents) -> Option<f32> {
+        match self.costs.get(&tile.value) {
+            Some(cost) => *cost,
+            None => Some(self.default_cost),
+        }
+    }
+
+    fn value(&self, identifier: &str) -> usize {
+        match self
+            .int_grid_values
+            .iter()
+            .find(|value| value.identifier.as_deref() == Some(identifier))
+        {
+            Some(value) => value.value,
+            None => panic!(
+                "No IntGrid value named {} is defined for the Values layer",
+                identifier
+            ),
+        }
+    }
+}
+
+/// An entry of the open set, ordered so the BinaryHeap pops the lowest estimate first
+struct Node {
+    estimate: f32,
+    idx: usize,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl DesignLevel {
+    /// Finds the cheapest path from `start` to `goal` with A*.
+    ///
+    /// `cost_fn` gives the cost of entering a tile or `None` when it is blocked, see `PathCosts`
+    /// for costs by IntGrid value. The start tile is never entered so it is free.
+    /// Returns `None` when either end is outside the level, the goal is blocked or can not be reached.
+    ///
+    /// Panics when `cost_fn` returns a negative cost.
+    pub fn find_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        movement: Movement,
+        cost_fn: impl Fn(&TileContents) -> Option<f32>,
+    ) -> Option<GridPath> {
+        if !self.in_bounds(start.0, start.1) || !self.in_bounds(goal.0, goal.1) {
+            return None;
+        }
+
+        let costs: Vec<Option<f32>> = self.level.iter().map(&cost_fn).collect();
+        if let Some(cost) = costs.iter().flatten().find(|cost| **cost < 0.0) {
+            panic!("Path costs can not be negative, got {}", cost)
+        }
+        let start_idx = gridpx_to_idx(start, self.width);
+        let goal_idx = gridpx_to_idx(goal, self.width);
+        costs[goal_idx]?;
+
+        // scaling the distance by the cheapest tile keeps the heuristic from overestimating
+        let min_cost = costs
+            .iter()
+            .flatten()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+        let heuristic = |(x, y): (usize, usize)| {
+            let dx = x.abs_diff(goal.0) as f32;
+            let dy = y.abs_diff(goal.1) as f32;
+            let distance = match movement {
+                Movement::FourWay => dx + dy,
+                Movement::EightWay(_) => dx.max(dy) + (DIAGONAL_COST - 1.0) * dx.min(dy),
+            };
+            distance * min_cost
+        };
+        let offsets: &[(isize, isize)] = match movement {
+            Movement::FourWay => &NEIGHBOURS4,
+            Movement::EightWay(_) => &NEIGHBOURS8,
+        };
+        let open = |x: usize, y: usize| self.in_bounds(x, y) && costs[y * self.width + x].is_some();
+
+        let mut best = vec![f32::INFINITY; self.level.len()];
+        let mut came_from = vec![usize::MAX; self.level.len()];
+        let mut heap = BinaryHeap::new();
+        best[start_idx] = 0.0;
+        heap.push(Node {
+            estimate: heuristic(start),
+            idx: start_idx,
+        });
+
+        while let Some(Node { estimate, idx }) = heap.pop() {
+            let (x, y) = (idx % self.width, idx / self.width);
+            if idx == goal_idx {
+                return Some(GridPath {
+                    cells: self.trace_back(&came_from, start_idx, goal_idx),
+                    cost: best[goal_idx],
+                });
+            }
+            if estimate > best[idx] + heuristic((x, y)) {
+                // a cheaper way here was already expanded
+                continue;
+            }
+
+            for (dx, dy) in offsets {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(*dx), y.checked_add_signed(*dy))
+                else {
+                    continue;
+                };
+                if !open(nx, ny) {
+                    continue;
+                }
+                let diagonal = *dx != 0 && *dy != 0;
+                if diagonal {
+                    let passed_by = [open(nx, y), open(x, ny)];
+                    let allowed = match movement {
+                        Movement::EightWay(CornerCutting::Allow) | Movement::FourWay => true,
+                        Movement::EightWay(CornerCutting::IfOneOpen) => passed_by.contains(&true),
+                        Movement::EightWay(CornerCutting::Never) => !passed_by.contains(&false),
+                    };
+                    if !allowed {
+                        continue;
+                    }
+                }
+
+                let next = ny * self.width + nx;
+                let step =
+                    costs[next].unwrap_or_default() * if diagonal { DIAGONAL_COST } else { 1.0 };
+                let cost = best[idx] + step;
+                if cost < best[next] {
+                    best[next] = cost;
+                    came_from[next] = idx;
+                    heap.push(Node {
+                        estimate: cost + heuristic((nx, ny)),
+                        idx: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn trace_back(
+        &self,
+        came_from: &[usize],
+        start_idx: usize,
+        goal_idx: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut cells = vec![];
+        let mut idx = goal_idx;
+        loop {
+            cells.push((idx % self.width, idx / self.width));
+            if idx == start_idx {
+                break;
+            }
+            idx = came_from[idx];
+        }
+        cells.reverse();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CornerCutting, Movement, PathCosts};
+    use crate::test_support::ascii_level;
+
+    #[test]
+    fn test_four_way_path() {
+        let level = ascii_level(&[
+            "....", //
+            ".##.", //
+            "...#", //
+        ]);
+        let costs = PathCosts::new(&level).block("wall");
+        let path = level
+            .find_path((0, 1), (3, 0), Movement::FourWay, |t| costs.tile_cost(t))
+            .unwrap();
+
+        assert_eq!(path.cells(), &[(0, 1), (0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(path.cost(), 4.0);
+        assert!(level
+            .find_path((0, 0), (3, 2), Movement::FourWay, |t| costs.tile_cost(t))
+            .is_none());
+        assert!(level
+            .find_path((0, 0), (4, 0), Movement::FourWay, |t| costs.tile_cost(t))
+            .is_none());
+    }
+
+    #[test]
+    fn test_corner_cutting() {
+        let level = ascii_level(&[
+            ".#", //
+            "..", //
+        ]);
+        let costs = PathCosts::new(&level).block("wall");
+        let path = |corners| {
+            level
+                .find_path((0, 0), (1, 1), Movement::EightWay(corners), |t| {
+                    costs.tile_cost(t)
+                })
+                .unwrap()
+        };
+
+        assert_eq!(path(CornerCutting::Allow).cells().len(), 2);
+        assert_eq!(path(CornerCutting::IfOneOpen).cells().len(), 2);
+        assert_eq!(
+            path(CornerCutting::Never).cells(),
+            &[(0, 0), (0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_value_costs() {
+        let level = ascii_level(&[
+            "...", //
+            "~~.", //
+            "...", //
+        ]);
+        let cheap_mud = PathCosts::new(&level).cost("mud", 1.0);
+        let path = level
+            .find_path((0, 0), (0, 2), Movement::FourWay, |t| {
+                cheap_mud.tile_cost(t)
+            })
+            .unwrap();
+        assert_eq!(path.cost(), 2.0);
+
+        let slow_mud = PathCosts::new(&level).cost("mud", 10.0);
+        let path = level
+            .find_path((0, 0), (0, 2), Movement::FourWay, |t| slow_mud.tile_cost(t))
+            .unwrap();
+        assert_eq!(path.cost(), 6.0);
+        assert!(path.cells().contains(&(2, 1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unknown_value_name() {
+        let level = ascii_level(&["."]);
+        let _ = PathCosts::new(&level).block("lava");
+    }
+}
@@ -0,0 +1,609 @@
+use serde_json::Map;
+
+use crate::{
+    design_map::DesignMap,
+    ldtk_helpers::{
+        atlas_index_to_src, atlas_index_to_tile_id, new_iid, write_external_levels, write_raw_world,
+    },
+    ldtk_structs::{Defs, Entity, Header, LDtk, Layer, LayerInstance, Level, Tileset},
+    ldtk_writer::{new_entity_instance, new_grid_tile},
+};
+
+/// The LDtk version of the files created by the ProjectBuilder
+const LDTK_VERSION: &str = "1.3.4";
+
+/// Colors handed out to IntGrid values and entities in the order they are defined
+const PALETTE: [&str; 8] = [
+    "#BE4A2F", "#3E8948", "#124E89", "#FEAE34", "#68386C", "#2CE8F5", "#E43B44", "#C0CBDC",
+];
+
+/// Builds a new LDtk project from scratch, for procedural content or test fixtures.
+///
+/// Example Usage:
+/// ```no_run
+/// use ldtk_map::prelude::*;
+///
+/// ProjectBuilder::new()
+///     .tileset("Forest", "atlas/forest.png", (128, 128), 8)
+///     .entities_layer("Entities", 8)
+///     .int_grid_layer("Values", 8, &["wall", "water"])
+///     .tiles_layer("Ground", 8, "Forest")
+///     .entity("Monster1", (8, 8), &["Enemy"])
+///     .level(
+///         LevelBuilder::new("Level_0", (64, 64))
+///             .tile("Ground", (0, 0), 3)
+///             .value("Values", (1, 0), 1)
+///             .entity("Entities", (2, 2), "Monster1"),
+///     )
+///     .save("maps/generated.ldtk");
+/// ```
+pub struct ProjectBuilder {
+    next_uid: i64,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+    entities: Vec<Entity>,
+    levels: Vec<LevelBuilder>,
+    external_levels: bool,
+}
+
+/// A level to add to a ProjectBuilder, the contents are placed on the layers by their identifier
+pub struct LevelBuilder {
+    identifier: String,
+    world_px: (i64, i64),
+    world_depth: i64,
+    size_px: (usize, usize),
+    tiles: Vec<(String, (usize, usize), usize)>,
+    values: Vec<(String, (usize, usize), usize)>,
+    entities: Vec<(String, (usize, usize), String)>,
+}
+
+impl LevelBuilder {
+    /// Creates an empty level named `identifier` that is `size_px` (width, height) pixels large
+    pub fn new(identifier: impl ToString, size_px: (usize, usize)) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            world_px: (0, 0),
+            world_depth: 0,
+            size_px,
+            tiles: vec![],
+            values: vec![],
+            entities: vec![],
+        }
+    }
+
+    /// The position of the level in the world, in pixels
+    pub fn world_position(mut self, world_x: i64, world_y: i64) -> Self {
+        self.world_px = (world_x, world_y);
+        self
+    }
+
+    /// The depth of the level in the world, levels with a higher depth are drawn over the others
+    pub fn world_depth(mut self, world_depth: i64) -> Self {
+        self.world_depth = world_depth;
+        self
+    }
+
+    /// Places the sprite at `atlas_index` on the tile (x, y) of the Tiles layer `layer`
+    pub fn tile(mut self, layer: impl ToString, xy: (usize, usize), atlas_index: usize) -> Self {
+        self.tiles.push((layer.to_string(), xy, atlas_index));
+        self
+    }
+
+    /// Sets the tile (x, y) of the IntGrid layer `layer` to `value`
+    pub fn value(mut self, layer: impl ToString, xy: (usize, usize), value: usize) -> Self {
+        self.values.push((layer.to_string(), xy, value));
+        self
+    }
+
+    /// Places the entity `identifier` on the tile (x, y) of the Entities layer `layer`
+    pub fn entity(
+        mut self,
+        layer: impl ToString,
+        xy: (usize, usize),
+        identifier: impl ToString,
+    ) -> Self {
+        self.entities
+            .push((layer.to_string(), xy, identifier.to_string()));
+        self
+    }
+}
+
+impl Default for ProjectBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectBuilder {
+    /// Creates a project without any definitions or levels
+    pub fn new() -> Self {
+        Self {
+            next_uid: 1,
+            tilesets: vec![],
+            layers: vec![],
+            entities: vec![],
+            levels: vec![],
+            external_levels: false,
+        }
+    }
+
+    fn uid(&mut self) -> i64 {
+        let uid = self.next_uid;
+        self.next_uid += 1;
+        uid
+    }
+
+    /// Adds a tileset whose atlas is found at `rel_path`, relative to where the project is saved.
+    /// `size_px` is the (width, height) of the atlas image
+    pub fn tileset(
+        mut self,
+        identifier: impl ToString,
+        rel_path: impl ToString,
+        size_px: (i64, i64),
+        tile_grid_size: i64,
+    ) -> Self {
+        let uid = self.uid() as usize;
+        self.tilesets.push(Tileset {
+            c_wid: Some(size_px.0 / tile_grid_size),
+            c_hei: Some(size_px.1 / tile_grid_size),
+            identifier: identifier.to_string(),
+            uid,
+            rel_path: Some(rel_path.to_string()),
+            embed_atlas: None,
+            px_wid: Some(size_px.0),
+            px_hei: Some(size_px.1),
+            tile_grid_size: Some(tile_grid_size),
+            spacing: Some(0),
+            padding: Some(0),
+            tags: Some(vec![]),
+            tags_source_enum_uid: None,
+            enum_tags: Some(vec![]),
+            custom_data: Some(vec![]),
+            saved_selections: Some(vec![]),
+            cached_pixel_data: None,
+            extra: Map::new(),
+        });
+        self
+    }
+
+    /// Adds a Tiles layer drawing from the tileset named `tileset`.
+    /// Layers are ordered from the top so add them in the order they should be drawn over each other
+    pub fn tiles_layer(mut self, identifier: impl ToString, grid_size: i64, tileset: &str) -> Self {
+        let tileset_uid = match self.tilesets.iter().find(|t| t.identifier == tileset) {
+            Some(tileset) => tileset.uid as i64,
+            None => panic!(
+                "Tileset {} must be added before a layer can use it.",
+                tileset
+            ),
+        };
+        let layer = self.new_layer(identifier, "Tiles", grid_size, vec![], Some(tileset_uid));
+        self.layers.push(layer);
+        self
+    }
+
+    /// Adds an IntGrid layer, `values` are the identifiers of the values starting from 1
+    pub fn int_grid_layer(
+        mut self,
+        identifier: impl ToString,
+        grid_size: i64,
+        values: &[&str],
+    ) -> Self {
+        let int_grid_values = values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                Some(serde_json::json!({
+                    "value": idx + 1,
+                    "identifier": value,
+                    "color": PALETTE[idx % PALETTE.len()],
+                    "tile": null,
+                }))
+            })
+            .collect();
+        let layer = self.new_layer(identifier, "IntGrid", grid_size, int_grid_values, None);
+        self.layers.push(layer);
+        self
+    }
+
+    /// Adds an Entities layer
+    pub fn entities_layer(mut self, identifier: impl ToString, grid_size: i64) -> Self {
+        let mut layer = self.new_layer(identifier, "Entities", grid_size, vec![], None);
+        layer.inactive_opacity = Some(0.6);
+        layer.hide_fields_when_inactive = Some(true);
+        self.layers.push(layer);
+        self
+    }
+
+    fn new_layer(
+        &mut self,
+        identifier: impl ToString,
+        layer_type: &str,
+        grid_size: i64,
+        int_grid_values: Vec<Option<serde_json::Value>>,
+        tileset_def_uid: Option<i64>,
+    ) -> Layer {
+        Layer {
+            layer_type: Some(layer_type.to_string()),
+            identifier: Some(identifier.to_string()),
+            purple_type: Some(layer_type.to_string()),
+            uid: Some(self.uid()),
+            doc: None,
+            ui_color: None,
+            grid_size: Some(grid_size),
+            guide_grid_wid: Some(0),
+            guide_grid_hei: Some(0),
+            display_opacity: Some(1.0),
+            inactive_opacity: Some(1.0),
+            hide_in_list: Some(false),
+            hide_fields_when_inactive: Some(false),
+            can_select_when_inactive: Some(true),
+            render_in_world_view: Some(true),
+            px_offset_x: Some(0),
+            px_offset_y: Some(0),
+            parallax_factor_x: Some(0.0),
+            parallax_factor_y: Some(0.0),
+            parallax_scaling: Some(true),
+            required_tags: Some(vec![]),
+            excluded_tags: Some(vec![]),
+            int_grid_values: Some(int_grid_values),
+            auto_rule_groups: Some(vec![]),
+            auto_source_layer_def_uid: None,
+            tileset_def_uid,
+            tile_pivot_x: Some(0.0),
+            tile_pivot_y: Some(0.0),
+            extra: Map::new(),
+        }
+    }
+
+    /// Adds an entity definition that is `size_px` (width, height) pixels large
+    pub fn entity(mut self, identifier: impl ToString, size_px: (i64, i64), tags: &[&str]) -> Self {
+        let uid = self.uid();
+        let color = PALETTE[self.entities.len() % PALETTE.len()];
+        self.entities.push(Entity {
+            identifier: Some(identifier.to_string()),
+            uid: Some(uid),
+            tags: Some(
+                tags.iter()
+                    .map(|tag| Some(serde_json::json!(tag)))
+                    .collect(),
+            ),
+            export_to_toc: Some(false),
+            doc: None,
+            width: Some(size_px.0),
+            height: Some(size_px.1),
+            resizable_x: Some(false),
+            resizable_y: Some(false),
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            keep_aspect_ratio: Some(false),
+            tile_opacity: Some(1.0),
+            fill_opacity: Some(1.0),
+            line_opacity: Some(1.0),
+            hollow: Some(false),
+            color: Some(color.to_string()),
+            render_mode: Some("Rectangle".to_string()),
+            show_name: Some(true),
+            tileset_id: None,
+            tile_render_mode: Some("FitInside".to_string()),
+            tile_rect: None,
+            nine_slice_borders: Some(vec![]),
+            max_count: Some(0),
+            limit_scope: Some("PerLevel".to_string()),
+            limit_behavior: Some("MoveLastOne".to_string()),
+            pivot_x: Some(0.0),
+            pivot_y: Some(0.0),
+            field_defs: Some(vec![]),
+            extra: Map::new(),
+        });
+        self
+    }
+
+    /// Adds a level, levels must have unique names
+    pub fn level(mut self, level: LevelBuilder) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    /// Saves every level in its own .ldtkl file in a folder named after the project,
+    /// like the "Save levels to separate files" option of LDtk
+    pub fn external_levels(mut self, external_levels: bool) -> Self {
+        self.external_levels = external_levels;
+        self
+    }
+
+    /// Writes the project as a .ldtk file at `path`
+    pub fn save(self, path: impl ToString) {
+        let path = path.to_string();
+        let mut ldtk_world = self.into_raw_world();
+        if ldtk_world.external_levels == Some(true) {
+            write_external_levels(&mut ldtk_world, &path);
+        }
+        write_raw_world(&ldtk_world, path);
+    }
+
+    /// Creates the DesignMap of the project without writing it to a file
+    pub fn build(self) -> DesignMap {
+        DesignMap::from_raw_world(&self.into_raw_world())
+    }
+
+    fn into_raw_world(mut self) -> LDtk {
+        let level_builders = std::mem::take(&mut self.levels);
+        let levels = level_builders
+            .into_iter()
+            .map(|level| self.build_level(level))
+            .collect();
+
+        LDtk {
+            header: Some(Header {
+                file_type: Some("LDtk Project JSON".to_string()),
+                app: Some("LDtk".to_string()),
+                doc: Some("https://ldtk.io/json".to_string()),
+                schema: Some("https://ldtk.io/files/JSON_SCHEMA.json".to_string()),
+                app_author: Some("Sebastien 'deepnight' Benard".to_string()),
+                app_version: Some(LDTK_VERSION.to_string()),
+                url: Some("https://ldtk.io".to_string()),
+            }),
+            iid: Some(new_iid()),
+            json_version: Some(LDTK_VERSION.to_string()),
+            app_build_id: Some(467698),
+            next_uid: Some(self.next_uid),
+            identifier_style: Some("Capitalize".to_string()),
+            toc: Some(vec![]),
+            world_layout: Some("Free".to_string()),
+            world_grid_width: Some(256),
+            world_grid_height: Some(256),
+            default_level_width: Some(256),
+            default_level_height: Some(256),
+            default_pivot_x: Some(0.0),
+            default_pivot_y: Some(0.0),
+            default_grid_size: Some(16),
+            bg_color: Some("#40465B".to_string()),
+            default_level_bg_color: Some("#696A79".to_string()),
+            minify_json: Some(false),
+            external_levels: Some(self.external_levels),
+            export_tiled: Some(false),
+            simplified_export: Some(false),
+            image_export_mode: Some("None".to_string()),
+            export_level_bg: Some(true),
+            png_file_pattern: None,
+            backup_on_save: Some(false),
+            backup_limit: Some(10),
+            backup_rel_path: None,
+            level_name_pattern: Some("Level_%idx".to_string()),
+            tutorial_desc: None,
+            custom_commands: Some(vec![]),
+            flags: Some(vec![]),
+            defs: Defs {
+                layers: Some(self.layers),
+                entities: Some(self.entities),
+                tilesets: self.tilesets,
+                enums: Some(vec![]),
+                external_enums: Some(vec![]),
+                level_fields: Some(vec![]),
+                extra: Map::new(),
+            },
+            levels,
+            worlds: Some(vec![]),
+            dummy_world_iid: Some(new_iid()),
+            extra: Map::new(),
+        }
+    }
+
+    fn build_level(&mut self, builder: LevelBuilder) -> Level {
+        let level_uid = self.uid();
+        let layer_instances = self
+            .layers
+            .iter()
+            .map(|layer| self.build_layer_instance(layer, &builder, level_uid))
+            .collect();
+
+        Level {
+            identifier: builder.identifier,
+            iid: Some(new_iid()),
+            uid: Some(level_uid),
+            world_x: builder.world_px.0,
+            world_y: builder.world_px.1,
+            world_depth: Some(builder.world_depth),
+            px_wid: Some(builder.size_px.0 as i64),
+            px_hei: Some(builder.size_px.1 as i64),
+            bg_color: Some("#696A79".to_string()),
+            level_bg_color: None,
+            use_auto_identifier: Some(false),
+            bg_rel_path: None,
+            level_bg_pos: None,
+            bg_pivot_x: Some(0.5),
+            bg_pivot_y: Some(0.5),
+            smart_color: Some("#ADADB5".to_string()),
+            bg_pos: None,
+            external_rel_path: None,
+            field_instances: Some(vec![]),
+            layer_instances: Some(layer_instances),
+            neighbours: Some(vec![]),
+            extra: Map::new(),
+        }
+    }
+
+    fn build_layer_instance(
+        &self,
+        layer: &Layer,
+        level: &LevelBuilder,
+        level_uid: i64,
+    ) -> LayerInstance {
+        let identifier = layer.identifier.clone().unwrap_or_default();
+        let layer_type = layer.purple_type.clone().unwrap_or_default();
+        let grid_size = layer.grid_size.unwrap_or(16) as usize;
+        let width = level.size_px.0.div_ceil(grid_size);
+        let height = level.size_px.1.div_ceil(grid_size);
+        let tileset = layer
+            .tileset_def_uid
+            .and_then(|uid| self.tilesets.iter().find(|t| t.uid as i64 == uid));
+        let in_layer = |layer_name: &String, (x, y): (usize, usize)| {
+            if layer_name.ne(&identifier) {
+                return false;
+            }
+            if x >= width || y >= height {
+                panic!(
+                    "({}, {}) is outside of the {} layer in level {}",
+                    x, y, identifier, level.identifier
+                )
+            }
+            true
+        };
+
+        let grid_tiles = level
+            .tiles
+            .iter()
+            .filter(|(layer_name, xy, _)| in_layer(layer_name, *xy))
+            .map(|(_, (x, y), atlas_index)| {
+                new_grid_tile(
+                    x + y * width,
+                    width,
+                    grid_size,
+                    atlas_index_to_src(*atlas_index, grid_size),
+                    0,
+                    atlas_index_to_tile_id(
+                        *atlas_index,
+                        tileset.and_then(|t| t.c_wid).unwrap_or(1) as usize,
+                    ),
+                )
+            })
+            .collect();
+
+        let mut int_grid_csv = vec![];
+        if layer_type == "IntGrid" {
+            int_grid_csv = vec![0; width * height];
+            for (_, (x, y), value) in level.values.iter().filter(|(l, xy, _)| in_layer(l, *xy)) {
+                int_grid_csv[x + y * width] = *value;
+            }
+        }
+
+        let entity_instances = level
+            .entities
+            .iter()
+            .filter(|(layer_name, xy, _)| in_layer(layer_name, *xy))
+            .map(|(_, xy, entity)| {
+                match self
+                    .entities
+                    .iter()
+                    .find(|def| def.identifier.as_deref() == Some(entity.as_str()))
+                {
+                    Some(def) => new_entity_instance(def, *xy, grid_size),
+                    None => panic!(
+                        "Entity {} must be added before it can be placed in level {}.",
+                        entity, level.identifier
+                    ),
+                }
+            })
+            .collect();
+
+        LayerInstance {
+            identifier,
+            layer_instance_type: layer_type,
+            width,
+            height,
+            grid_size,
+            opacity: Some(1.0),
+            px_total_offset_x: Some(0),
+            px_total_offset_y: Some(0),
+            tileset_def_uid: tileset.map(|t| t.uid),
+            tileset_rel_path: tileset.and_then(|t| t.rel_path.clone()),
+            iid: Some(new_iid()),
+            level_id: Some(level_uid),
+            layer_def_uid: layer.uid,
+            px_offset_x: Some(0),
+            px_offset_y: Some(0),
+            visible: true,
+            optional_rules: Some(vec![]),
+            int_grid_csv: Some(int_grid_csv),
+            auto_layer_tiles: Some(vec![]),
+            seed: Some(level_uid * 1000 + layer.uid.unwrap_or_default()),
+            override_tileset_uid: None,
+            grid_tiles: Some(grid_tiles),
+            entity_instances: Some(entity_instances),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::LevelBuilder;
+    use crate::{
+        design_map::DesignMap,
+        test_support::{forest_project, TempPath},
+    };
+
+    #[test]
+    fn test_build_project() {
+        let world = forest_project(8, &["wall", "water"])
+            .level(
+                LevelBuilder::new("Level_0", (32, 24))
+                    .world_position(64, 0)
+                    .tile("Ground", (1, 0), 0)
+                    .value("Values", (2, 1), 2)
+                    .entity("Entities", (3, 2), "Monster1"),
+            )
+            .build();
+
+        let level = &world.levels()["Level_0"];
+        assert_eq!((level.width(), level.height()), (4, 3));
+        assert_eq!(level.tileset_name(), "Forest");
+        assert_eq!(level.world_tile_x(), 8);
+        assert!(level.level()[1].has_tile());
+        assert!(!level.level()[0].has_tile());
+        assert_eq!(level.level()[6].value(), 2);
+        assert_eq!(level.level()[11].entity_name(), Some("Monster1"));
+        assert_eq!(level.level()[11].entity_tag(), Some("Enemy"));
+        assert_eq!(level.level()[11].entity_tags(), ["Enemy"]);
+    }
+
+    #[test]
+    fn test_save_and_load_project() {
+        let path = TempPath::new("builder.ldtk");
+        forest_project(8, &["wall", "water"])
+            .level(LevelBuilder::new("Level_0", (16, 16)).value("Values", (1, 1), 1))
+            .level(LevelBuilder::new("Level_1", (8, 8)).world_position(16, 0))
+            .save(&path);
+
+        let world = DesignMap::load(&path);
+        assert_eq!(world.levels().len(), 2);
+        assert_eq!(world.levels()["Level_0"].level()[3].value(), 1);
+        assert_eq!(world.levels()["Level_1"].width(), 1);
+    }
+
+    #[test]
+    fn test_save_external_levels() {
+        let dir = TempPath::dir("external");
+        let path = dir.join("world.ldtk");
+        forest_project(8, &["wall", "water"])
+            .external_levels(true)
+            .level(LevelBuilder::new("Level_0", (16, 16)).value("Values", (1, 1), 1))
+            .save(&path);
+
+        assert!(Path::new(&dir.join("world/Level_0.ldtkl")).exists());
+        let mut world = DesignMap::load(&path);
+        assert_eq!(world.levels()["Level_0"].level()[3].value(), 1);
+
+        // saving into the project writes the levels next to the new file
+        world.levels_mut().get_mut("Level_0").unwrap().level[0].value = 2;
+        let out = dir.join("edited.ldtk");
+        world.save_into(&path, &out);
+        assert!(Path::new(&dir.join("edited/Level_0.ldtkl")).exists());
+        let edited = DesignMap::load(&out);
+        assert_eq!(edited.levels()["Level_0"].level()[0].value(), 2);
+        assert_eq!(edited.levels()["Level_0"].level()[3].value(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    /// Tests entities can not be placed without a definition
+    fn test_undefined_entity() {
+        let _ = forest_project(8, &["wall", "water"])
+            .level(LevelBuilder::new("Level_0", (16, 16)).entity("Entities", (0, 0), "Ghost"))
+            .build();
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use crate::{
+    design_map::{DesignLevel, TileContents},
+    fields::FieldValue,
+};
+
+/// Builds a game object for an entity placed in LDtk, adding it to the game through `Ctx`
+type Factory<Ctx> = Box<dyn Fn(&mut Ctx, &EntitySpawn)>;
+
+/// Spawns the entities of levels through factories registered by entity name or tag,
+/// instead of matching on `TileContents::entity_name` by hand.
+///
+/// Example Usage:
+/// ```no_run
+/// use ldtk_map::prelude::*;
+///
+/// struct Game {
+///     monsters: Vec<(usize, usize, i64)>,
+/// }
+///
+/// let registry = EntityRegistry::new()
+///     .register("Monster1", |game: &mut Game, spawn| {
+///         let hp = spawn.field("hp").and_then(|hp| hp.as_int()).unwrap_or(10);
+///         let (x, y) = spawn.grid_xy();
+///         game.monsters.push((x, y, hp));
+///     })
+///     .register_tag("Decoration", |_game, _spawn| {});
+///
+/// let map = DesignMap::load("maps/world.ldtk");
+/// let mut game = Game { monsters: vec![] };
+/// let report = registry.spawn_level(&map.levels()["Level_0"], &mut game);
+/// for unknown in report.unknown() {
+///     eprintln!("no factory for {} at {:?}", unknown.name(), unknown.grid_xy());
+/// }
+/// ```
+pub struct EntityRegistry<Ctx> {
+    by_name: HashMap<String, Factory<Ctx>>,
+    by_tag: HashMap<String, Factory<Ctx>>,
+}
+
+/// An entity handed to a factory of an `EntityRegistry`
+pub struct EntitySpawn<'a> {
+    tile: &'a TileContents,
+    grid_xy: (usize, usize),
+    world_px: (i64, i64),
+}
+
+/// What happened while spawning the entities of a level
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpawnReport {
+    spawned: usize,
+    unknown: Vec<UnknownEntity>,
+}
+
+/// An entity no factory was registered for, neither by name nor by tag
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownEntity {
+    name: String,
+    grid_xy: (usize, usize),
+}
+
+impl<Ctx> Default for EntityRegistry<Ctx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ctx> EntityRegistry<Ctx> {
+    /// Creates a registry without any factories
+    pub fn new() -> Self {
+        Self {
+            by_name: HashMap::new(),
+            by_tag: HashMap::new(),
+        }
+    }
+
+    /// Spawns entities named `name` with `factory`, the name can be given as in LDtk or
+    /// as returned by `TileContents::entity_name`
+    pub fn register(
+        mut self,
+        name: impl ToString,
+        factory: impl Fn(&mut Ctx, &EntitySpawn) + 'static,
+    ) -> Self {
+        self.by_name
+            .insert(name.to_string().replace('_', " "), Box::new(factory));
+        self
+    }
+
+    /// Spawns entities tagged `tag` with `factory` when no factory is registered for their name.
+    /// Entities with several registered tags use the first of them in the order of their definition.
+    pub fn register_tag(
+        mut self,
+        tag: impl ToString,
+        factory: impl Fn(&mut Ctx, &EntitySpawn) + 'static,
+    ) -> Self {
+        self.by_tag.insert(tag.to_string(), Box::new(factory));
+        self
+    }
+
+    /// Whether a factory is registered for entities named `name`
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.by_name.contains_key(&name.replace('_', " "))
+    }
+
+    /// Hands every entity of `level` to its factory, from the top left tile row by row.
+    ///
+    /// Entities without a factory are skipped and listed in the report.
+    pub fn spawn_level(&self, level: &DesignLevel, ctx: &mut Ctx) -> SpawnReport {
+        let mut report = SpawnReport::default();
+        let (world_x, world_y) = level.world_px();
+        let grid_size = level.grid_size_px() as i64;
+
+        for ((x, y), tile) in level.iter_coords() {
+            let Some(name) = tile.entity_name() else {
+                continue;
+            };
+            let factory = self.by_name.get(name).or_else(|| {
+                tile.entity_tags()
+                    .iter()
+                    .find_map(|tag| self.by_tag.get(tag))
+            });
+            let Some(factory) = factory else {
+                report.unknown.push(UnknownEntity {
+                    name: name.to_string(),
+                    grid_xy: (x, y),
+                });
+                continue;
+            };
+
+            let spawn = EntitySpawn {
+                tile,
+                grid_xy: (x, y),
+                world_px: (
+                    world_x + x as i64 * grid_size,
+                    world_y + y as i64 * grid_size,
+                ),
+            };
+            factory(ctx, &spawn);
+            report.spawned += 1;
+        }
+        report
+    }
+}
+
+impl EntitySpawn<'_> {
+    /// The name of the entity, as in `TileContents::entity_name`
+    pub fn name(&self) -> &str {
+        self.tile.entity_name().unwrap_or_default()
+    }
+
+    /// The first tag of the entity
+    pub fn tag(&self) -> Option<&str> {
+        self.tile.entity_tag()
+    }
+
+    /// The tags of the entity in the order of its definition
+    pub fn tags(&self) -> &[String] {
+        self.tile.entity_tags()
+    }
+
+    /// The unique id LDtk gave the entity
+    pub fn iid(&self) -> Option<&str> {
+        self.tile.entity_iid()
+    }
+
+    /// The x and y of the tile of the entity in its level
+    pub fn grid_xy(&self) -> (usize, usize) {
+        self.grid_xy
+    }
+
+    /// The x and y coordinates of the top left corner of the tile of the entity in the world, in pixels
+    pub fn world_px(&self) -> (i64, i64) {
+        self.world_px
+    }
+
+    /// The fields set on the entity in LDtk by their identifier
+    pub fn fields(&self) -> &HashMap<String, FieldValue> {
+        self.tile.entity_fields()
+    }
+
+    /// The field named `identifier`
+    pub fn field(&self, identifier: &str) -> Option<&FieldValue> {
+        self.tile.entity_field(identifier)
+    }
+
+    /// The whole tile the entity is on, for its sprite or IntGrid value
+    pub fn tile(&self) -> &TileContents {
+        self.tile
+    }
+}
+
+impl SpawnReport {
+    /// The amount of entities handed to a factory
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
+    /// The entities skipped as no factory was registered for them
+    pub fn unknown(&self) -> &[UnknownEntity] {
+        &self.unknown
+    }
+
+    /// Whether every entity of the level had a factory
+    pub fn is_complete(&self) -> bool {
+        self.unknown.is_empty()
+    }
+}
+
+impl UnknownEntity {
+    /// The name of the entity
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// The x and y of the tile of the entity in its level
+    pub fn grid_xy(&self) -> (usize, usize) {
+        self.grid_xy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::EntityRegistry;
+    use crate::{design_map::DesignMap, test_support::patched};
+
+    fn load_project(name: &str) -> DesignMap {
+        let name = format!("registry_{}.ldtk", name);
+        let path = patched("./tests/testmaps/entities.ldtk", &name, |project| {
+            let entities = &mut project["levels"][0]["layerInstances"][1]["entityInstances"];
+            entities[0]["fieldInstances"] =
+                json!([{ "__identifier": "hp", "__type": "Int", "__value": 25 }]);
+            entities[1]["__identifier"] = json!("Gold_Chest");
+            entities[1]["__tags"] = json!(["Shiny", "Loot"]);
+        });
+        DesignMap::load(&path)
+    }
+
+    #[test]
+    fn test_spawn_by_name_and_tag() {
+        let map = load_project("spawn");
+        let level = &map.levels()["Level_0"];
+        let registry = EntityRegistry::new()
+            .register("Monster1", |spawned: &mut Vec<String>, spawn| {
+                let hp = spawn.field("hp").and_then(|hp| hp.as_int()).unwrap();
+                spawned.push(format!("monster {} at {:?}", hp, spawn.world_px()));
+            })
+            // not the first tag of the chest, every tag is tried
+            .register_tag("Loot", |spawned: &mut Vec<String>, spawn| {
+                let tags = spawn.tags().join(", ");
+                spawned.push(format!(
+                    "{} ({}) at {:?}",
+                    spawn.name(),
+                    tags,
+                    spawn.grid_xy()
+                ));
+            });
+
+        let mut spawned = vec![];
+        let report = registry.spawn_level(level, &mut spawned);
+        assert!(report.is_complete());
+        assert_eq!(report.spawned(), 2);
+        assert_eq!(
+            spawned,
+            vec!["monster 25 at (8, 8)", "Gold Chest (Shiny, Loot) at (3, 0)"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_entities_reported() {
+        let map = load_project("unknown");
+        let level = &map.levels()["Level_0"];
+        let registry = EntityRegistry::new().register("Monster_1", |count: &mut usize, _| {
+            *count += 1;
+        });
+        assert!(registry.is_registered("Monster 1"));
+
+        let mut count = 0;
+        let report = registry.spawn_level(level, &mut count);
+        assert_eq!(count, 0);
+        assert_eq!(report.spawned(), 0);
+        let names: Vec<_> = report.unknown().iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["Monster1", "Gold Chest"]);
+    }
+}
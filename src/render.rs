@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+
+use image::{imageops, Rgba, RgbaImage};
+
+use crate::design_map::{DesignLevel, DesignTileset, TileLayer};
+
+/// Color of the outline drawn around entities
+const ENTITY_BOX_COLOR: Rgba<u8> = Rgba([255, 0, 255, 255]);
+/// How strongly IntGrid colors are blended over the tiles, out of 255
+const INT_GRID_OPACITY: u8 = 128;
+
+/// Extra information to draw on top of the tiles when rendering a level
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    int_grid_overlay: bool,
+    entity_boxes: bool,
+}
+
+impl RenderOptions {
+    /// Renders only the tiles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blend the colors of the IntGrid values over the tiles
+    pub fn int_grid_overlay(mut self, int_grid_overlay: bool) -> Self {
+        self.int_grid_overlay = int_grid_overlay;
+        self
+    }
+
+    /// Outline the tiles that have an entity on them
+    pub fn entity_boxes(mut self, entity_boxes: bool) -> Self {
+        self.entity_boxes = entity_boxes;
+        self
+    }
+}
+
+impl DesignLevel {
+    /// Renders the tiles of the level into a png at `out_path`.
+    ///
+    /// Every visible Tiles and AutoLayer layer is drawn in the order of the layers in LDtk,
+    /// the Ground layer as it is now in the level and the others as they were loaded.
+    /// The atlas images are looked for in `atlas_dir` as described in `DesignTileset::load_image`.
+    pub fn render_png(&self, atlas_dir: impl ToString, out_path: impl ToString) {
+        self.render_png_with(atlas_dir, out_path, &RenderOptions::new());
+    }
+
+    /// Renders the level into a png at `out_path` with the extra information in `options`
+    pub fn render_png_with(
+        &self,
+        atlas_dir: impl ToString,
+        out_path: impl ToString,
+        options: &RenderOptions,
+    ) {
+        let out_path = out_path.to_string();
+        if let Err(e) = self.render_image(atlas_dir, options).save(&out_path) {
+            panic!(
+                "Should have been able to write the file: {} | Error: {}",
+                out_path, e
+            )
+        }
+    }
+
+    /// Renders the level into an image in memory, e.g. for thumbnails
+    pub fn render_image(&self, atlas_dir: impl ToString, options: &RenderOptions) -> RgbaImage {
+        let atlas_dir = atlas_dir.to_string();
+        let grid_size = self.grid_size_px as u32;
+        let mut canvas = RgbaImage::new(
+            self.width as u32 * grid_size,
+            self.height as u32 * grid_size,
+        );
+        let mut atlases = HashMap::new();
+        atlases.insert(self.tileset.uid, load_atlas(&self.tileset, &atlas_dir));
+
+        for depth in 0..=self.tile_layers.len() {
+            if depth == self.ground_depth {
+                self.draw_ground(&mut canvas, &atlases[&self.tileset.uid]);
+            }
+            if let Some(layer) = self.tile_layers.get(depth) {
+                let atlas = atlases
+                    .entry(layer.tileset.uid)
+                    .or_insert_with(|| load_atlas(&layer.tileset, &atlas_dir));
+                draw_layer(&mut canvas, atlas, layer);
+            }
+        }
+
+        for (idx, tile) in self.level.iter().enumerate() {
+            let x = (idx % self.width) as u32 * grid_size;
+            let y = (idx / self.width) as u32 * grid_size;
+
+            if options.int_grid_overlay && tile.value != 0 {
+                let color = self
+                    .int_grid_values
+                    .iter()
+                    .find(|value| value.value == tile.value)
+                    .and_then(|value| hex_to_rgb(&value.color))
+                    .unwrap_or([255, 255, 255]);
+                let overlay = RgbaImage::from_pixel(
+                    grid_size,
+                    grid_size,
+                    Rgba([color[0], color[1], color[2], INT_GRID_OPACITY]),
+                );
+                imageops::overlay(&mut canvas, &overlay, x as i64, y as i64);
+            }
+
+            if options.entity_boxes && tile.entity_name.is_some() {
+                for i in 0..grid_size {
+                    for (px, py) in [
+                        (x + i, y),
+                        (x + i, y + grid_size - 1),
+                        (x, y + i),
+                        (x + grid_size - 1, y + i),
+                    ] {
+                        canvas.put_pixel(px, py, ENTITY_BOX_COLOR);
+                    }
+                }
+            }
+        }
+
+        canvas
+    }
+
+    /// Draws the sprites of the Ground layer, fully opaque
+    fn draw_ground(&self, canvas: &mut RgbaImage, atlas: &RgbaImage) {
+        for (idx, tile) in self.level.iter().enumerate() {
+            if !tile.has_tile {
+                continue;
+            }
+            let src = self
+                .tileset
+                .atlas_index_to_src(tile.atlas_index, self.grid_size_px);
+            let xy = (
+                (idx % self.width * self.grid_size_px) as i64,
+                (idx / self.width * self.grid_size_px) as i64,
+            );
+            let sprite = sprite(atlas, src, self.grid_size_px, tile.flip_bits, 1.0);
+            imageops::overlay(canvas, &sprite, xy.0, xy.1);
+        }
+    }
+}
+
+/// Draws the sprites of `layer` moved by its offset
+fn draw_layer(canvas: &mut RgbaImage, atlas: &RgbaImage, layer: &TileLayer) {
+    for tile in layer.tiles.iter() {
+        let alpha = tile.alpha * layer.opacity;
+        let sprite = sprite(atlas, tile.src, layer.grid_size_px, tile.flip_bits, alpha);
+        imageops::overlay(
+            canvas,
+            &sprite,
+            tile.px.0 as i64 + layer.offset_px.0,
+            tile.px.1 as i64 + layer.offset_px.1,
+        );
+    }
+}
+
+/// Copies the `size` px sprite at `src` out of the atlas, flipped by `flip_bits` and made `alpha` opaque
+fn sprite(
+    atlas: &RgbaImage,
+    src: (usize, usize),
+    size: usize,
+    flip_bits: u8,
+    alpha: f64,
+) -> RgbaImage {
+    let size = size as u32;
+    let mut sprite = imageops::crop_imm(atlas, src.0 as u32, src.1 as u32, size, size).to_image();
+    if flip_bits & 1 != 0 {
+        imageops::flip_horizontal_in_place(&mut sprite);
+    }
+    if flip_bits & 2 != 0 {
+        imageops::flip_vertical_in_place(&mut sprite);
+    }
+    if alpha < 1.0 {
+        for pixel in sprite.pixels_mut() {
+            pixel[3] = (pixel[3] as f64 * alpha.max(0.0)).round() as u8;
+        }
+    }
+    sprite
+}
+
+fn load_atlas(tileset: &DesignTileset, atlas_dir: &str) -> RgbaImage {
+    match tileset.load_image(atlas_dir) {
+        Ok(image) => image.atlas,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Converts a color in the `#RRGGBB` format LDtk uses into its channels
+fn hex_to_rgb(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{hex_to_rgb, RenderOptions, ENTITY_BOX_COLOR, INT_GRID_OPACITY};
+    use crate::{
+        design_map::DesignMap,
+        project_builder::{LevelBuilder, ProjectBuilder},
+        test_support::{patched, TempPath},
+    };
+
+    #[test]
+    fn test_render_level() {
+        let world = DesignMap::load("./tests/testmaps/two_tileatlases.ldtk");
+        let image =
+            world.levels()["Level_0"].render_image("./tests/testmaps", &RenderOptions::new());
+        let atlas = image::open("./tests/atlas/1x1green.png")
+            .unwrap()
+            .to_rgba8();
+
+        assert_eq!(image.dimensions(), (256, 256));
+        assert_eq!(image.get_pixel(0, 0), atlas.get_pixel(0, 0));
+        assert_eq!(image.get_pixel(255, 255), atlas.get_pixel(7, 7));
+    }
+
+    #[test]
+    fn test_render_flips_and_overlays() {
+        let dir = TempPath::dir("render");
+        // a 2x2 px sprite with a red top left corner
+        let mut atlas = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        atlas.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        atlas.save(dir.join("atlas.png")).unwrap();
+
+        let mut world = ProjectBuilder::new()
+            .tileset("Atlas", "atlas.png", (2, 2), 2)
+            .entities_layer("Entities", 2)
+            .int_grid_layer("Values", 2, &["wall"])
+            .tiles_layer("Ground", 2, "Atlas")
+            .entity("Monster1", (2, 2), &[])
+            .level(
+                LevelBuilder::new("Level_0", (8, 2))
+                    .tile("Ground", (0, 0), 0)
+                    .tile("Ground", (1, 0), 0)
+                    .entity("Entities", (2, 0), "Monster1")
+                    .value("Values", (3, 0), 1),
+            )
+            .build();
+        let level = world.levels_mut().get_mut("Level_0").unwrap();
+        level.level_mut()[1].set_flip(true, true);
+
+        let out = dir.join("level.png");
+        level.render_png_with(
+            &dir,
+            &out,
+            &RenderOptions::new()
+                .int_grid_overlay(true)
+                .entity_boxes(true),
+        );
+        let image = image::open(&out).unwrap().to_rgba8();
+
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(1, 1), &Rgba([0, 0, 0, 255]));
+        // flipped on both axis moves the red corner to the bottom right
+        assert_eq!(image.get_pixel(2, 0), &Rgba([0, 0, 0, 255]));
+        assert_eq!(image.get_pixel(3, 1), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(4, 0), &ENTITY_BOX_COLOR);
+        assert_eq!(image.get_pixel(5, 1), &ENTITY_BOX_COLOR);
+        let wall = hex_to_rgb(level.int_grid_values()[0].color()).unwrap();
+        let overlay = image.get_pixel(6, 0);
+        assert_eq!([overlay[0], overlay[1], overlay[2]], wall);
+        assert_eq!(overlay[3], INT_GRID_OPACITY);
+    }
+
+    #[test]
+    fn test_render_layers_in_order() {
+        let dir = TempPath::dir("render_layers");
+        // a red and a blue 2x2 px sprite
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let mut atlas = RgbaImage::from_pixel(4, 2, red);
+        for (x, y) in [(2, 0), (3, 0), (2, 1), (3, 1)] {
+            atlas.put_pixel(x, y, blue);
+        }
+        atlas.save(dir.join("atlas.png")).unwrap();
+
+        let project = dir.join("project.ldtk");
+        ProjectBuilder::new()
+            .tileset("Atlas", "atlas.png", (4, 2), 2)
+            .entities_layer("Entities", 2)
+            .tiles_layer("Decor", 2, "Atlas")
+            .int_grid_layer("Values", 2, &["wall"])
+            .tiles_layer("Ground", 2, "Atlas")
+            .tiles_layer("Backdrop", 2, "Atlas")
+            .level(
+                LevelBuilder::new("Level_0", (8, 2))
+                    .tile("Ground", (0, 0), 0)
+                    .tile("Ground", (1, 0), 0)
+                    .tile("Backdrop", (0, 0), 1)
+                    .tile("Backdrop", (2, 0), 1)
+                    .tile("Decor", (1, 0), 1),
+            )
+            .save(&project);
+
+        let world = DesignMap::load(&project);
+        let image = world.levels()["Level_0"].render_image(&dir, &RenderOptions::new());
+        // the Backdrop is hidden by the Ground but shows where Ground has no sprite
+        assert_eq!(image.get_pixel(0, 0), &red);
+        assert_eq!(image.get_pixel(4, 0), &blue);
+        assert_eq!(image.get_pixel(2, 0), &blue);
+        assert_eq!(image.get_pixel(6, 0), &Rgba([0, 0, 0, 0]));
+
+        // the same sprite placed by the rules of an AutoLayer, half transparent
+        let auto = patched(&project, "render_layers_auto.ldtk", |project| {
+            let decor = &mut project["levels"][0]["layerInstances"][1];
+            decor["__type"] = "AutoLayer".into();
+            decor["autoLayerTiles"] = decor["gridTiles"].take();
+            decor["autoLayerTiles"][0]["a"] = 0.5.into();
+            decor["gridTiles"] = serde_json::json!([]);
+        });
+        let world = DesignMap::load(&auto);
+        let level = &world.levels()["Level_0"];
+        assert_eq!(level.ground_depth(), 1);
+        assert_eq!(level.tile_layers()[1].name(), "Decor");
+        let image = level.render_image(&dir, &RenderOptions::new());
+        let blended = image.get_pixel(2, 0);
+        assert!(blended[0] > 100 && blended[2] > 100, "{:?}", blended);
+    }
+
+    #[test]
+    fn test_hex_to_rgb() {
+        assert_eq!(hex_to_rgb("#BE4A2F"), Some([0xBE, 0x4A, 0x2F]));
+        assert_eq!(hex_to_rgb("BE4A2F"), None);
+    }
+}
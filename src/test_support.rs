@@ -0,0 +1,39 @@
+//! Fixtures shared by the tests of the other modules
+
+mod temp;
+
+pub(crate) use temp::{patched, TempPath};
+
+use crate::{
+    design_map::DesignLevel,
+    project_builder::{LevelBuilder, ProjectBuilder},
+};
+
+/// A project with the Entities, Values and Ground layers all on a `grid_size` grid,
+/// the `Forest` tileset as a 32x16 px atlas and a `Monster1` entity tagged `Enemy`.
+/// Nothing loads the atlas image, so it does not exist.
+pub(crate) fn forest_project(grid_size: i64, values: &[&str]) -> ProjectBuilder {
+    ProjectBuilder::new()
+        .tileset("Forest", "forest.png", (32, 16), grid_size)
+        .entities_layer("Entities", grid_size)
+        .int_grid_layer("Values", grid_size, values)
+        .tiles_layer("Ground", grid_size, "Forest")
+        .entity("Monster1", (grid_size, grid_size), &["Enemy"])
+}
+
+/// Builds a level of 8px tiles from rows where `#` is a wall and `~` is mud,
+/// the values 1 and 2 of the Values layer
+pub(crate) fn ascii_level(rows: &[&str]) -> DesignLevel {
+    let mut level = LevelBuilder::new("Level_0", (rows[0].len() * 8, rows.len() * 8));
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '#' => level = level.value("Values", (x, y), 1),
+                '~' => level = level.value("Values", (x, y), 2),
+                _ => {}
+            }
+        }
+    }
+    let mut world = forest_project(8, &["wall", "mud"]).level(level).build();
+    world.levels.remove("Level_0").unwrap()
+}
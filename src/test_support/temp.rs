@@ -0,0 +1,66 @@
+//! Files written by tests, kept free of the rest of the crate so the tests of
+//! ldtk_map_derive can include this file as well
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Copies the ldtk file at `source` to a `TempPath` named `name`, changing its json with `patch`
+pub(crate) fn patched(
+    source: &str,
+    name: &str,
+    patch: impl FnOnce(&mut serde_json::Value),
+) -> TempPath {
+    let mut project: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(source).unwrap()).unwrap();
+    patch(&mut project);
+    let path = TempPath::new(name);
+    std::fs::write(&path.0, project.to_string()).unwrap();
+    path
+}
+
+/// A path in the temp directory unique to the test process, removed with everything
+/// below it when dropped
+pub(crate) struct TempPath(PathBuf);
+
+impl TempPath {
+    /// `ldtk_map_<pid>_<name>` in the temp directory, nothing is created yet
+    pub(crate) fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("ldtk_map_{}_{}", std::process::id(), name)))
+    }
+
+    /// Like `new`, creating the path as a folder
+    pub(crate) fn dir(name: &str) -> Self {
+        let dir = Self::new(name);
+        std::fs::create_dir_all(&dir.0).unwrap();
+        dir
+    }
+
+    /// The path of `name` inside this folder
+    pub(crate) fn join(&self, name: &str) -> String {
+        self.0.join(name).display().to_string()
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl fmt::Display for TempPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.display().fmt(f)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = if self.0.is_dir() {
+            std::fs::remove_dir_all(&self.0)
+        } else {
+            std::fs::remove_file(&self.0)
+        };
+    }
+}
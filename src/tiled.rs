@@ -0,0 +1,525 @@
+use std::{fmt::Write, fs, path::Path};
+
+use serde_json::{json, Value};
+
+use crate::{
+    design_map::{DesignLevel, DesignMap, ENTITIES, GROUND, VALUES},
+    ldtk_helpers::ATLAS_WIDTH,
+};
+
+const TILED_VERSION: &str = "1.10.2";
+const TILED_FORMAT_VERSION: &str = "1.10";
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+
+/// The file formats a level can be exported to for the Tiled map editor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TiledFormat {
+    /// Tiled's JSON map format, `.tmj`
+    Tmj,
+    /// Tiled's XML map format, `.tmx`
+    Tmx,
+}
+
+impl TiledFormat {
+    /// The file extension used by the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TiledFormat::Tmj => "tmj",
+            TiledFormat::Tmx => "tmx",
+        }
+    }
+}
+
+/// A tileset of the exported map, the IntGrid values get a tileset without an image
+struct TiledTileset {
+    firstgid: u32,
+    name: String,
+    tile_size: usize,
+    tilecount: usize,
+    columns: usize,
+    spacing: usize,
+    margin: usize,
+    image: Option<(String, usize, usize)>,
+    values: Vec<usize>,
+}
+
+/// The level converted into what Tiled needs, written out as either format
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    tilesets: Vec<TiledTileset>,
+    ground: Vec<u32>,
+    values: Option<Vec<u32>>,
+    objects: Vec<TiledObject>,
+}
+
+struct TiledObject {
+    id: usize,
+    name: String,
+    object_type: String,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl DesignLevel {
+    /// Converts the level into a Tiled map in `format`.
+    ///
+    /// The Ground layer becomes a tile layer, the Values layer a tile layer using an "IntGrid"
+    /// tileset whose tiles carry a `value` property, and the Entities an object layer.
+    /// Objects get the size LDtk gave the entity, or one tile for entities placed with `set_entity`.
+    /// The path to the atlas is kept relative to the ldtk file so save the map next to it.
+    pub fn to_tiled(&self, format: TiledFormat) -> String {
+        let map = self.tiled_map();
+        match format {
+            TiledFormat::Tmj => write_tmj(&map),
+            TiledFormat::Tmx => write_tmx(&map),
+        }
+    }
+
+    fn tiled_map(&self) -> TiledMap {
+        let tileset = &self.tileset;
+        let tile_size = self.grid_size_px;
+        let columns = match tileset.columns() {
+            0 => ATLAS_WIDTH,
+            columns => columns,
+        };
+        let local_id =
+            |atlas_index: usize| atlas_index % ATLAS_WIDTH + atlas_index / ATLAS_WIDTH * columns;
+        // without the size of the atlas the tiles used decide how many it holds
+        let used = self
+            .level
+            .iter()
+            .filter(|tile| tile.has_tile)
+            .map(|tile| local_id(tile.atlas_index) + 1)
+            .max()
+            .unwrap_or_default();
+        let atlas_tileset = TiledTileset {
+            firstgid: 1,
+            name: tileset.identifier.clone(),
+            tile_size,
+            tilecount: (columns * tileset.rows()).max(used).max(1),
+            columns,
+            spacing: tileset.spacing,
+            margin: tileset.padding,
+            image: Some((
+                tileset
+                    .rel_path
+                    .clone()
+                    .or_else(|| {
+                        tileset
+                            .embed_atlas
+                            .as_ref()
+                            .map(|name| format!("{}.png", name))
+                    })
+                    .unwrap_or_default(),
+                tileset.width_px,
+                tileset.height_px,
+            )),
+            values: vec![],
+        };
+
+        let ground = self
+            .level
+            .iter()
+            .map(|tile| {
+                if !tile.has_tile {
+                    return 0;
+                }
+                let mut gid = atlas_tileset.firstgid + local_id(tile.atlas_index) as u32;
+                if tile.flip_x() {
+                    gid |= FLIPPED_HORIZONTALLY;
+                }
+                if tile.flip_y() {
+                    gid |= FLIPPED_VERTICALLY;
+                }
+                gid
+            })
+            .collect();
+
+        let mut tilesets = vec![atlas_tileset];
+        let max_value = self
+            .level
+            .iter()
+            .map(|tile| tile.value)
+            .max()
+            .unwrap_or_default();
+        let values = (max_value > 0).then(|| {
+            let firstgid = tilesets[0].firstgid + tilesets[0].tilecount as u32;
+            tilesets.push(TiledTileset {
+                firstgid,
+                name: "IntGrid".to_string(),
+                tile_size,
+                tilecount: max_value,
+                columns: 0,
+                spacing: 0,
+                margin: 0,
+                image: None,
+                values: (1..=max_value).collect(),
+            });
+            self.level
+                .iter()
+                .map(|tile| match tile.value {
+                    0 => 0,
+                    value => firstgid + value as u32 - 1,
+                })
+                .collect()
+        });
+
+        let objects = self
+            .level
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, tile)| Some((idx, tile, tile.entity_name.clone()?)))
+            .enumerate()
+            .map(|(id, (idx, tile, name))| TiledObject {
+                id: id + 1,
+                name,
+                object_type: tile.entity_tag().unwrap_or_default().to_string(),
+                x: idx % self.width * tile_size,
+                y: idx / self.width * tile_size,
+                width: tile.entity_size().map_or(tile_size, |(width, _)| width),
+                height: tile.entity_size().map_or(tile_size, |(_, height)| height),
+            })
+            .collect();
+
+        TiledMap {
+            width: self.width,
+            height: self.height,
+            tile_size,
+            tilesets,
+            ground,
+            values,
+            objects,
+        }
+    }
+}
+
+impl DesignMap {
+    /// Exports every level as its own Tiled map named after the level into `out_dir`
+    pub fn export_tiled(&self, out_dir: impl ToString, format: TiledFormat) {
+        let out_dir = out_dir.to_string();
+        let out_dir = Path::new(&out_dir);
+        for (name, level) in self.levels.iter() {
+            let path = out_dir.join(format!("{}.{}", name, format.extension()));
+            if let Err(e) = fs::write(&path, level.to_tiled(format)) {
+                panic!(
+                    "Should have been able to write the file: {} | Error: {}",
+                    path.display(),
+                    e
+                )
+            }
+        }
+    }
+}
+
+fn write_tmj(map: &TiledMap) -> String {
+    let tilesets: Vec<Value> = map
+        .tilesets
+        .iter()
+        .map(|tileset| {
+            let mut value = json!({
+                "firstgid": tileset.firstgid,
+                "name": tileset.name,
+                "tilewidth": tileset.tile_size,
+                "tileheight": tileset.tile_size,
+                "tilecount": tileset.tilecount,
+                "columns": tileset.columns,
+                "spacing": tileset.spacing,
+                "margin": tileset.margin,
+            });
+            if let Some((image, width, height)) = &tileset.image {
+                value["image"] = json!(image);
+                value["imagewidth"] = json!(width);
+                value["imageheight"] = json!(height);
+            }
+            if !tileset.values.is_empty() {
+                value["tiles"] = tileset
+                    .values
+                    .iter()
+                    .map(|v| {
+                        json!({
+                            "id": v - 1,
+                            "properties": [{ "name": "value", "type": "int", "value": v }],
+                        })
+                    })
+                    .collect();
+            }
+            value
+        })
+        .collect();
+
+    let tile_layer = |id: usize, name: &str, data: &[u32]| {
+        json!({
+            "id": id,
+            "name": name,
+            "type": "tilelayer",
+            "width": map.width,
+            "height": map.height,
+            "x": 0,
+            "y": 0,
+            "opacity": 1,
+            "visible": true,
+            "data": data,
+        })
+    };
+    let mut layers = vec![tile_layer(1, GROUND, &map.ground)];
+    if let Some(values) = &map.values {
+        layers.push(tile_layer(2, VALUES, values));
+    }
+    let objects: Vec<Value> = map
+        .objects
+        .iter()
+        .map(|object| {
+            json!({
+                "id": object.id,
+                "name": object.name,
+                "type": object.object_type,
+                "x": object.x,
+                "y": object.y,
+                "width": object.width,
+                "height": object.height,
+                "rotation": 0,
+                "visible": true,
+            })
+        })
+        .collect();
+    layers.push(json!({
+        "id": 3,
+        "name": ENTITIES,
+        "type": "objectgroup",
+        "draworder": "topdown",
+        "x": 0,
+        "y": 0,
+        "opacity": 1,
+        "visible": true,
+        "objects": objects,
+    }));
+
+    let tmj = json!({
+        "type": "map",
+        "version": TILED_FORMAT_VERSION,
+        "tiledversion": TILED_VERSION,
+        "orientation": "orthogonal",
+        "renderorder": "right-down",
+        "infinite": false,
+        "compressionlevel": -1,
+        "width": map.width,
+        "height": map.height,
+        "tilewidth": map.tile_size,
+        "tileheight": map.tile_size,
+        "nextlayerid": 4,
+        "nextobjectid": map.objects.len() + 1,
+        "tilesets": tilesets,
+        "layers": layers,
+    });
+    serde_json::to_string_pretty(&tmj).unwrap_or_default()
+}
+
+fn write_tmx(map: &TiledMap) -> String {
+    let mut tmx = String::new();
+    let _ = writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        tmx,
+        r#"<map version="{}" tiledversion="{}" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="0" nextlayerid="4" nextobjectid="{}">"#,
+        TILED_FORMAT_VERSION,
+        TILED_VERSION,
+        map.width,
+        map.height,
+        map.tile_size,
+        map.tile_size,
+        map.objects.len() + 1
+    );
+
+    for tileset in map.tilesets.iter() {
+        let _ = writeln!(
+            tmx,
+            r#" <tileset firstgid="{}" name="{}" tilewidth="{}" tileheight="{}" spacing="{}" margin="{}" tilecount="{}" columns="{}">"#,
+            tileset.firstgid,
+            escape(&tileset.name),
+            tileset.tile_size,
+            tileset.tile_size,
+            tileset.spacing,
+            tileset.margin,
+            tileset.tilecount,
+            tileset.columns
+        );
+        if let Some((image, width, height)) = &tileset.image {
+            let _ = writeln!(
+                tmx,
+                r#"  <image source="{}" width="{}" height="{}"/>"#,
+                escape(image),
+                width,
+                height
+            );
+        }
+        for value in tileset.values.iter() {
+            let _ = writeln!(
+                tmx,
+                r#"  <tile id="{}"><properties><property name="value" type="int" value="{}"/></properties></tile>"#,
+                value - 1,
+                value
+            );
+        }
+        let _ = writeln!(tmx, " </tileset>");
+    }
+
+    let mut tile_layer = |id: usize, name: &str, data: &[u32]| {
+        let _ = writeln!(
+            tmx,
+            r#" <layer id="{}" name="{}" width="{}" height="{}">"#,
+            id, name, map.width, map.height
+        );
+        let _ = writeln!(tmx, r#"  <data encoding="csv">"#);
+        let rows: Vec<String> = data
+            .chunks(map.width.max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|gid| gid.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        let _ = writeln!(tmx, "{}", rows.join(",\n"));
+        let _ = writeln!(tmx, "  </data>");
+        let _ = writeln!(tmx, " </layer>");
+    };
+    tile_layer(1, GROUND, &map.ground);
+    if let Some(values) = &map.values {
+        tile_layer(2, VALUES, values);
+    }
+
+    let _ = writeln!(tmx, r#" <objectgroup id="3" name="{}">"#, ENTITIES);
+    for object in map.objects.iter() {
+        let _ = writeln!(
+            tmx,
+            r#"  <object id="{}" name="{}" type="{}" x="{}" y="{}" width="{}" height="{}"/>"#,
+            object.id,
+            escape(&object.name),
+            escape(&object.object_type),
+            object.x,
+            object.y,
+            object.width,
+            object.height
+        );
+    }
+    let _ = writeln!(tmx, " </objectgroup>");
+    let _ = writeln!(tmx, "</map>");
+    tmx
+}
+
+/// Escapes text to be used inside of a xml attribute
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TiledFormat;
+    use crate::{
+        design_map::DesignMap,
+        project_builder::{LevelBuilder, ProjectBuilder},
+        test_support::forest_project,
+    };
+
+    #[test]
+    fn test_level_to_tmj() {
+        let world = DesignMap::load("./tests/testmaps/two_tileatlases.ldtk");
+        let tmj: serde_json::Value =
+            serde_json::from_str(&world.levels()["Level_1"].to_tiled(TiledFormat::Tmj)).unwrap();
+
+        assert_eq!(tmj["width"], 32);
+        assert_eq!(tmj["tilesets"][0]["name"], "SecondTileset");
+        assert_eq!(tmj["tilesets"][0]["image"], "../atlas/1x1brown.png");
+        assert_eq!(tmj["layers"][0]["data"][0], 1);
+        assert_eq!(tmj["layers"][0]["data"].as_array().unwrap().len(), 32 * 32);
+    }
+
+    #[test]
+    fn test_values_and_entities() {
+        let world = forest_project(8, &["wall", "water"])
+            .level(
+                LevelBuilder::new("Level_0", (16, 16))
+                    .tile("Ground", (0, 0), 17)
+                    .value("Values", (1, 1), 2)
+                    .entity("Entities", (1, 0), "Monster1"),
+            )
+            .build();
+        let level = &world.levels()["Level_0"];
+
+        let tmj: serde_json::Value =
+            serde_json::from_str(&level.to_tiled(TiledFormat::Tmj)).unwrap();
+        // atlas index 17 is the second sprite of the second row of a 4 wide atlas
+        assert_eq!(tmj["layers"][0]["data"][0], 1 + 4 + 1);
+        assert_eq!(tmj["tilesets"][1]["firstgid"], 9);
+        assert_eq!(tmj["layers"][1]["data"][3], 9 + 1);
+        assert_eq!(tmj["layers"][2]["objects"][0]["name"], "Monster1");
+        assert_eq!(tmj["layers"][2]["objects"][0]["type"], "Enemy");
+        assert_eq!(tmj["layers"][2]["objects"][0]["x"], 8);
+
+        let tmx = level.to_tiled(TiledFormat::Tmx);
+        assert!(tmx.contains(r#"<tileset firstgid="9" name="IntGrid""#));
+        assert!(tmx.contains(r#"<object id="1" name="Monster1" type="Enemy" x="8" y="0""#));
+        assert!(tmx.contains("6,0,\n0,0"));
+    }
+
+    #[test]
+    fn test_atlas_without_size() {
+        let world = ProjectBuilder::new()
+            .tileset("Unknown", "unknown.png", (0, 0), 8)
+            .entities_layer("Entities", 8)
+            .int_grid_layer("Values", 8, &["wall"])
+            .tiles_layer("Ground", 8, "Unknown")
+            .level(
+                LevelBuilder::new("Level_0", (16, 16))
+                    .tile("Ground", (0, 0), 17)
+                    .value("Values", (1, 1), 1),
+            )
+            .build();
+
+        let tmj: serde_json::Value =
+            serde_json::from_str(&world.levels()["Level_0"].to_tiled(TiledFormat::Tmj)).unwrap();
+        // the atlas is taken as 16 sprites wide and has to hold the sprite at index 17
+        assert_eq!(tmj["tilesets"][0]["tilecount"], 18);
+        assert_eq!(tmj["layers"][0]["data"][0], 1 + 17);
+        assert_eq!(tmj["tilesets"][1]["firstgid"], 19);
+        assert_eq!(tmj["layers"][1]["data"][3], 19);
+    }
+
+    #[test]
+    fn test_entity_size() {
+        let mut world = forest_project(8, &[])
+            .entity("Door", (8, 16), &[])
+            .level(
+                LevelBuilder::new("Level_0", (16, 16))
+                    .entity("Entities", (0, 0), "Door")
+                    .entity("Entities", (1, 0), "Monster1"),
+            )
+            .build();
+        let level = world.levels.get_mut("Level_0").unwrap();
+        level.level_mut()[1].set_entity(Some("Chest"));
+
+        let tmj: serde_json::Value =
+            serde_json::from_str(&level.to_tiled(TiledFormat::Tmj)).unwrap();
+        // without values there is no Values layer so the entities are the second layer
+        let objects = &tmj["layers"][1]["objects"];
+        assert_eq!(
+            (&objects[0]["width"], &objects[0]["height"]),
+            (&8.into(), &16.into())
+        );
+        // placed with set_entity so only the grid cell is known
+        assert_eq!(
+            (&objects[1]["width"], &objects[1]["height"]),
+            (&8.into(), &8.into())
+        );
+
+        let tmx = level.to_tiled(TiledFormat::Tmx);
+        assert!(tmx.contains(r#"name="Door" type="" x="0" y="0" width="8" height="16"/>"#));
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use image::{imageops, RgbaImage};
+
+use crate::design_map::DesignTileset;
+
+/// The atlases built into LDtk by their `embed_atlas` name and the file LDtk ships them as
+const EMBED_ATLAS_FILES: [(&str, &str); 1] = [("LdtkIcons", "finalbossblues-icons_full_16.png")];
+
+/// The decoded atlas of a tileset, sliced into the sprites `TileContents::atlas_index` refers to
+#[derive(Clone, Debug)]
+pub struct TilesetImage {
+    pub(crate) tileset: DesignTileset,
+    pub(crate) atlas: RgbaImage,
+}
+
+impl DesignTileset {
+    /// Decodes the atlas image of the tileset.
+    ///
+    /// The image is looked for in `atlas_dir` by the path LDtk stores for the tileset,
+    /// which is relative to the ldtk file, or else by the atlas file name alone.
+    /// LDtk does not save the atlases built into it with the project, so copy their image into
+    /// `atlas_dir` named after `embed_atlas`, e.g. `LdtkIcons.png`, or by the file name LDtk ships it as.
+    /// Errors when the image is missing or can not be decoded.
+    pub fn load_image(&self, atlas_dir: impl ToString) -> Result<TilesetImage, String> {
+        let path = self.atlas_path(Path::new(&atlas_dir.to_string()))?;
+        match image::open(&path) {
+            Ok(atlas) => Ok(TilesetImage {
+                tileset: self.clone(),
+                atlas: atlas.to_rgba8(),
+            }),
+            Err(e) => Err(format!(
+                "Could not load the atlas for tileset {} at {} | Error: {}",
+                self.identifier,
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    fn atlas_path(&self, atlas_dir: &Path) -> Result<PathBuf, String> {
+        let rel_path = match (&self.rel_path, &self.embed_atlas) {
+            (Some(rel_path), _) => rel_path,
+            (None, Some(embed_atlas)) => return self.embed_atlas_path(atlas_dir, embed_atlas),
+            (None, None) => return Err(format!("Tileset {} has no atlas image.", self.identifier)),
+        };
+        let path = atlas_dir.join(rel_path);
+        if path.exists() {
+            return Ok(path);
+        }
+        Ok(match Path::new(rel_path).file_name() {
+            Some(file_name) => atlas_dir.join(file_name),
+            None => path,
+        })
+    }
+
+    fn embed_atlas_path(&self, atlas_dir: &Path, embed_atlas: &str) -> Result<PathBuf, String> {
+        let file_name = format!("{}.png", embed_atlas);
+        let shipped_name = EMBED_ATLAS_FILES
+            .iter()
+            .find(|(name, _)| *name == embed_atlas)
+            .map(|(_, shipped_name)| *shipped_name);
+        let path = [Some(file_name.as_str()), shipped_name]
+            .into_iter()
+            .flatten()
+            .map(|name| atlas_dir.join(name))
+            .find(|path| path.exists());
+        path.ok_or_else(|| {
+            format!(
+                "Tileset {} uses the {} atlas built into LDtk, copy its image from LDtk into {} as {}",
+                self.identifier,
+                embed_atlas,
+                atlas_dir.display(),
+                file_name
+            )
+        })
+    }
+}
+
+impl TilesetImage {
+    /// The whole atlas image
+    pub fn atlas(&self) -> &RgbaImage {
+        &self.atlas
+    }
+
+    /// The tileset the atlas belongs to
+    pub fn tileset(&self) -> &DesignTileset {
+        &self.tileset
+    }
+
+    /// Copies the pixels of the sprite at `atlas_index`, leaving out the spacing and padding
+    /// around it, `None` if the index is outside of the atlas
+    pub fn tile_pixels(&self, atlas_index: usize) -> Option<RgbaImage> {
+        let size = self.tileset.tile_grid_size;
+        let (x, y) = self.tileset.atlas_index_to_src(atlas_index, size);
+        let (width, height) = self.atlas.dimensions();
+        if size == 0 || x + size > width as usize || y + size > height as usize {
+            return None;
+        }
+        Some(
+            imageops::crop_imm(&self.atlas, x as u32, y as u32, size as u32, size as u32)
+                .to_image(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use crate::{
+        design_map::{DesignMap, DesignTileset},
+        test_support::TempPath,
+    };
+
+    #[test]
+    fn test_tile_pixels() {
+        let world = DesignMap::load("./tests/testmaps/two_tileatlases.ldtk");
+        let level = &world.levels()["Level_0"];
+        let image = level.tileset().load_image("./tests/testmaps").unwrap();
+        let atlas = image::open("./tests/atlas/1x1green.png")
+            .unwrap()
+            .to_rgba8();
+
+        let tile = level.get(0, 0).unwrap();
+        let pixels = image.tile_pixels(tile.atlas_index()).unwrap();
+        assert_eq!(pixels.dimensions(), (8, 8));
+        assert_eq!(pixels.get_pixel(0, 0), atlas.get_pixel(0, 0));
+        assert_eq!(image.tile_pixels(16 * 100), None);
+    }
+
+    #[test]
+    fn test_tile_pixels_spacing_and_padding() {
+        let dir = TempPath::dir("tileset");
+        // 2x2 sprites with 1 px of padding and spacing, each filled with the color of its index
+        let mut atlas = RgbaImage::from_pixel(7, 7, Rgba([255, 255, 255, 255]));
+        for (idx, (x, y)) in [(1, 1), (4, 1), (1, 4), (4, 4)].into_iter().enumerate() {
+            for (px, py) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                atlas.put_pixel(x + px, y + py, Rgba([idx as u8, 0, 0, 255]));
+            }
+        }
+        atlas.save(dir.join("spaced.png")).unwrap();
+        let tileset = DesignTileset {
+            identifier: "Spaced".to_string(),
+            uid: 1,
+            rel_path: Some("../atlas/spaced.png".to_string()),
+            embed_atlas: None,
+            width_px: 7,
+            height_px: 7,
+            tile_grid_size: 2,
+            spacing: 1,
+            padding: 1,
+        };
+
+        let image = tileset.load_image(&dir).unwrap();
+        let top_right = image.tile_pixels(1).unwrap();
+        assert_eq!(top_right, RgbaImage::from_pixel(2, 2, Rgba([1, 0, 0, 255])));
+        let bottom_left = image.tile_pixels(16).unwrap();
+        assert_eq!(
+            bottom_left,
+            RgbaImage::from_pixel(2, 2, Rgba([2, 0, 0, 255]))
+        );
+        assert_eq!(image.tile_pixels(2), None);
+    }
+
+    #[test]
+    fn test_embedded_atlas() {
+        let dir = TempPath::dir("embedded_atlas");
+        let tileset = DesignTileset {
+            identifier: "Icons".to_string(),
+            uid: 1,
+            rel_path: None,
+            embed_atlas: Some("LdtkIcons".to_string()),
+            width_px: 32,
+            height_px: 16,
+            tile_grid_size: 16,
+            spacing: 0,
+            padding: 0,
+        };
+
+        let error = tileset.load_image(&dir).unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "Tileset Icons uses the LdtkIcons atlas built into LDtk, copy its image from LDtk into {} as LdtkIcons.png",
+                dir
+            )
+        );
+
+        // the second icon is blue
+        let mut atlas = RgbaImage::from_pixel(32, 16, Rgba([255, 0, 0, 255]));
+        for (x, y) in (16..32).flat_map(|x| (0..16).map(move |y| (x, y))) {
+            atlas.put_pixel(x, y, Rgba([0, 0, 255, 255]));
+        }
+        atlas
+            .save(dir.join("finalbossblues-icons_full_16.png"))
+            .unwrap();
+        let image = tileset.load_image(&dir).unwrap();
+        assert_eq!(
+            image.tile_pixels(1).unwrap(),
+            RgbaImage::from_pixel(16, 16, Rgba([0, 0, 255, 255]))
+        );
+
+        // a copy named after the atlas is used first
+        RgbaImage::from_pixel(32, 16, Rgba([0, 255, 0, 255]))
+            .save(dir.join("LdtkIcons.png"))
+            .unwrap();
+        let image = tileset.load_image(&dir).unwrap();
+        assert_eq!(image.atlas().get_pixel(0, 0), &Rgba([0, 255, 0, 255]));
+    }
+}
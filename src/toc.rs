@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    fields::{entity_field_types, FieldValue},
+    ldtk_structs::Defs,
+};
+
+/// The instances of one entity listed in the table of contents of the project,
+/// LDtk lists every entity whose definition has "Export to table of contents" checked
+#[derive(Clone, Debug, PartialEq)]
+pub struct TocEntry {
+    identifier: String,
+    instances: Vec<TocInstance>,
+}
+
+/// An entity instance listed in the table of contents, readable without its level
+#[derive(Clone, Debug, PartialEq)]
+pub struct TocInstance {
+    entity_iid: String,
+    layer_iid: String,
+    level_iid: String,
+    world_iid: String,
+    world_px: (i64, i64),
+    size_px: (usize, usize),
+    fields: HashMap<String, FieldValue>,
+}
+
+impl TocEntry {
+    /// The name of the entity, with `_` replaced by spaces like `TileContents::entity_name`
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_ref()
+    }
+
+    /// Every instance of the entity in the project
+    pub fn instances(&self) -> &[TocInstance] {
+        &self.instances
+    }
+}
+
+impl TocInstance {
+    /// The unique id of the entity instance
+    pub fn entity_iid(&self) -> &str {
+        self.entity_iid.as_ref()
+    }
+
+    /// The unique id of the layer the instance is on
+    pub fn layer_iid(&self) -> &str {
+        self.layer_iid.as_ref()
+    }
+
+    /// The unique id of the level the instance is in
+    pub fn level_iid(&self) -> &str {
+        self.level_iid.as_ref()
+    }
+
+    /// The unique id of the world the instance is in
+    pub fn world_iid(&self) -> &str {
+        self.world_iid.as_ref()
+    }
+
+    /// The x and y coordinates of the instance in the world, in pixels
+    pub fn world_px(&self) -> (i64, i64) {
+        self.world_px
+    }
+
+    /// The width and height of the instance, in pixels
+    pub fn size_px(&self) -> (usize, usize) {
+        self.size_px
+    }
+
+    /// The fields of the instance by their identifier
+    pub fn fields(&self) -> &HashMap<String, FieldValue> {
+        &self.fields
+    }
+
+    /// The field named `identifier`
+    pub fn field(&self, identifier: &str) -> Option<&FieldValue> {
+        self.fields.get(identifier)
+    }
+}
+
+/// Converts the raw table of contents of a project, already migrated to the LDtk 1.5 layout
+pub(crate) fn toc_entries(toc: &[Option<Value>], defs: &Defs) -> Vec<TocEntry> {
+    let field_types = entity_field_types(defs);
+    let no_types = HashMap::new();
+
+    toc.iter()
+        .flatten()
+        .filter_map(|entry| {
+            let identifier = entry["identifier"].as_str()?;
+            let types = field_types.get(identifier).unwrap_or(&no_types);
+            let instances = entry["instancesData"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|data| toc_instance(data, types))
+                .collect();
+            Some(TocEntry {
+                identifier: identifier.replace('_', " "),
+                instances,
+            })
+        })
+        .collect()
+}
+
+fn toc_instance(data: &Value, field_types: &HashMap<String, String>) -> TocInstance {
+    let iid = |key: &str| data["iids"][key].as_str().unwrap_or_default().to_string();
+    let size = |key: &str| data[key].as_u64().unwrap_or_default() as usize;
+    let fields = data["fields"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(identifier, value)| {
+            let field_type = field_types.get(identifier).map(|t| t.as_str());
+            (identifier.clone(), FieldValue::from_json(value, field_type))
+        })
+        .collect();
+
+    TocInstance {
+        entity_iid: iid("entityIid"),
+        layer_iid: iid("layerIid"),
+        level_iid: iid("levelIid"),
+        world_iid: iid("worldIid"),
+        world_px: (
+            data["worldX"].as_i64().unwrap_or_default(),
+            data["worldY"].as_i64().unwrap_or_default(),
+        ),
+        size_px: (size("widPx"), size("heiPx")),
+        fields,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{design_map::DesignMap, fields::FieldValue, test_support::patched};
+
+    fn load_project(name: &str, version: &str, toc: serde_json::Value) -> DesignMap {
+        let name = format!("toc_{}.ldtk", name);
+        let path = patched("./tests/testmaps/entities.ldtk", &name, |project| {
+            project["jsonVersion"] = json!(version);
+            project["toc"] = toc;
+            project["defs"]["entities"][0]["exportToToc"] = json!(true);
+            project["defs"]["entities"][0]["fieldDefs"] = json!([
+                { "identifier": "hp", "__type": "Float" },
+                { "identifier": "patrol", "__type": "Array<Point>" }
+            ]);
+        });
+        DesignMap::load(&path)
+    }
+
+    #[test]
+    fn test_toc() {
+        let toc = json!([{
+            "identifier": "Monster1",
+            "instancesData": [{
+                "iids": {
+                    "entityIid": "f3d9b410-1460-11ee-bd4a-cf8ef1312bae",
+                    "layerIid": "7896fc40-1460-11ee-bd4a-498e84ca939d",
+                    "levelIid": "72050250-1460-11ee-bd4a-07de8fdbff9c",
+                    "worldIid": "7204db40-1460-11ee-bd4a-01896227b672"
+                },
+                "worldX": 24, "worldY": 0, "widPx": 8, "heiPx": 8,
+                "fields": { "hp": 10, "patrol": [{ "cx": 1, "cy": 2 }] }
+            }]
+        }]);
+        let world = load_project("current", "1.5.3", toc);
+
+        let entry = &world.toc()[0];
+        assert_eq!(entry.identifier(), "Monster1");
+        let monster = &entry.instances()[0];
+        assert_eq!(monster.world_px(), (24, 0));
+        assert_eq!(monster.size_px(), (8, 8));
+        assert_eq!(monster.level_iid(), "72050250-1460-11ee-bd4a-07de8fdbff9c");
+        assert_eq!(monster.field("hp"), Some(&FieldValue::Float(10.0)));
+        assert_eq!(
+            monster.field("patrol").unwrap().as_array().unwrap()[0].as_point(),
+            Some((1, 2))
+        );
+    }
+
+    #[test]
+    fn test_toc_before_1_5() {
+        let toc = json!([{
+            "identifier": "Monster1",
+            "instances": [{
+                "entityIid": "f3d9b410-1460-11ee-bd4a-cf8ef1312bae",
+                "levelIid": "72050250-1460-11ee-bd4a-07de8fdbff9c"
+            }]
+        }]);
+        let world = load_project("old", "1.3.3", toc);
+
+        let monster = &world.toc()[0].instances()[0];
+        assert_eq!(monster.entity_iid(), "f3d9b410-1460-11ee-bd4a-cf8ef1312bae");
+        assert_eq!(monster.world_px(), (32, 8));
+    }
+}
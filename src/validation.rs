@@ -0,0 +1,298 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    design_map::{ENTITIES, GROUND, VALUES},
+    ldtk_helpers::{try_get_raw_world, ATLAS_WIDTH},
+    ldtk_structs::{LDtk, Level},
+};
+
+/// How bad a problem found by `validate` is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The map loads but may not behave as expected in game
+    Warning,
+    /// The map will fail to load or will load with wrong data
+    Error,
+}
+
+/// A single problem found in a LDtk file by `validate`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    location: String,
+    message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, location: impl ToString, message: impl ToString) -> Self {
+        Self {
+            severity,
+            location: location.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// How bad the problem is
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Where in the project the problem is, e.g. `Level_0 > Ground`
+    pub fn location(&self) -> &str {
+        self.location.as_ref()
+    }
+
+    /// What the problem is
+    pub fn message(&self) -> &str {
+        self.message.as_ref()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.location, self.message)
+    }
+}
+
+/// Checks the ldtk file at `path` against the conventions of this crate listed in the README,
+/// reporting every problem found instead of stopping at the first one like `DesignMap::load`.
+///
+/// An empty list means the map is safe to load.
+pub fn validate(path: impl ToString) -> Vec<Diagnostic> {
+    let path = path.to_string();
+    match try_get_raw_world(&path) {
+        Ok(ldtk_world) => validate_world(&ldtk_world),
+        Err(e) => vec![Diagnostic::new(Severity::Error, path, e)],
+    }
+}
+
+pub(crate) fn validate_world(ldtk_world: &LDtk) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for tileset in ldtk_world.defs.tilesets.iter() {
+        let columns = tileset
+            .c_wid
+            .or_else(|| Some(tileset.px_wid? / tileset.tile_grid_size?))
+            .unwrap_or_default();
+        if columns as usize > ATLAS_WIDTH {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                format!("tileset {}", tileset.identifier),
+                format!(
+                    "atlas is {} tiles wide but atlas indexes assume at most {}, tiles past that will share indexes",
+                    columns, ATLAS_WIDTH
+                ),
+            ));
+        }
+    }
+
+    let mut level_names: HashMap<&str, usize> = HashMap::new();
+    let mut first_grid_size: Option<(&str, usize)> = None;
+    for level in ldtk_world.levels.iter() {
+        *level_names.entry(&level.identifier).or_default() += 1;
+        validate_level(ldtk_world, level, &mut diagnostics);
+
+        let Some(ground) = level
+            .layer_instances
+            .iter()
+            .flatten()
+            .find(|layer| layer.identifier.eq(GROUND))
+        else {
+            continue;
+        };
+        match first_grid_size {
+            None => first_grid_size = Some((&level.identifier, ground.grid_size)),
+            Some((first_level, grid_size)) if grid_size != ground.grid_size => {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    format!("{} > {}", level.identifier, GROUND),
+                    format!(
+                        "layer uses a {}px grid but {} uses {}px, levels should all use the same size",
+                        ground.grid_size, first_level, grid_size
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut duplicates: Vec<_> = level_names
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .collect();
+    duplicates.sort();
+    for (name, count) in duplicates {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            name,
+            format!(
+                "{} levels share this name, level names must be unique",
+                count
+            ),
+        ));
+    }
+
+    diagnostics
+}
+
+fn validate_level(ldtk_world: &LDtk, level: &Level, diagnostics: &mut Vec<Diagnostic>) {
+    let layer = |identifier: &str| {
+        level
+            .layer_instances
+            .iter()
+            .flatten()
+            .find(|layer| layer.identifier.eq(identifier))
+    };
+
+    let Some(ground) = layer(GROUND) else {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            &level.identifier,
+            "missing the \"Ground\" layer",
+        ));
+        return;
+    };
+    let ground_location = format!("{} > {}", level.identifier, GROUND);
+
+    match ground.tileset_def_uid {
+        None => diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            &ground_location,
+            "layer does not use a tileset",
+        )),
+        Some(uid) => match ldtk_world.defs.tilesets.iter().find(|t| t.uid == uid) {
+            None => diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                &ground_location,
+                format!("tileset {} is not defined in the project", uid),
+            )),
+            Some(tileset) => {
+                if tileset
+                    .tile_grid_size
+                    .is_some_and(|size| size as usize != ground.grid_size)
+                {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        &ground_location,
+                        format!(
+                            "tileset {} uses {}px tiles but the layer uses {}px, atlas indexes will be wrong",
+                            tileset.identifier,
+                            tileset.tile_grid_size.unwrap_or_default(),
+                            ground.grid_size
+                        ),
+                    ))
+                }
+            }
+        },
+    }
+
+    for other in [ENTITIES, VALUES] {
+        if let Some(other_layer) = layer(other) {
+            if other_layer.grid_size != ground.grid_size {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    format!("{} > {}", level.identifier, other),
+                    format!(
+                        "layer uses a {}px grid but the Ground layer uses {}px, layers should all use the same size",
+                        other_layer.grid_size, ground.grid_size
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(entities) = layer(ENTITIES).and_then(|layer| layer.entity_instances.as_ref()) {
+        for entity in entities.iter() {
+            if entity.grid.len() != 2 || entity.grid.iter().any(|pos| *pos < 0) {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    format!(
+                        "{} > {} > {}",
+                        level.identifier, ENTITIES, entity.identifier
+                    ),
+                    format!("entity has an invalid grid position {:?}", entity.grid),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, validate_world, Severity};
+    use crate::ldtk_helpers::get_raw_world;
+
+    #[test]
+    fn test_valid_world() {
+        assert!(validate("./tests/testmaps/two_tileatlases.ldtk").is_empty());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let diagnostics = validate("wrong_path");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let mut world = get_raw_world("./tests/testmaps/bad_names.ldtk".to_string());
+        world.levels[1]
+            .layer_instances
+            .as_mut()
+            .unwrap()
+            .retain(|l| l.identifier != "Ground");
+
+        let diagnostics = validate_world(&world);
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(diagnostics.len(), 3, "{:?}", messages);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.severity() == Severity::Error)
+                .count(),
+            2
+        );
+        assert!(messages.iter().any(|m| m.contains("Ground")));
+        assert!(messages.iter().any(|m| m.contains("unique")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("tileset Internal_Icons")));
+    }
+
+    #[test]
+    fn test_mixed_grid_sizes() {
+        let diagnostics = validate("./tests/testmaps/entities.ldtk");
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity() == Severity::Warning));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.location() == "Level_0 > Entities"));
+    }
+
+    #[test]
+    fn test_grid_sizes_across_levels() {
+        let mut world = get_raw_world("./tests/testmaps/two_tileatlases.ldtk".to_string());
+        let ground = world.levels[1]
+            .layer_instances
+            .as_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|l| l.identifier == "Ground")
+            .unwrap();
+        ground.grid_size = 16;
+
+        let diagnostics = validate_world(&world);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity() == Severity::Warning));
+        assert!(diagnostics.iter().any(
+            |d| d.location() == "Level_1 > Ground" && d.message().contains("Level_0 uses 8px")
+        ));
+    }
+}
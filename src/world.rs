@@ -0,0 +1,183 @@
+use std::cmp::Reverse;
+
+use crate::design_map::{DesignLevel, DesignMap, TileContents};
+
+impl DesignLevel {
+    /// Whether the world pixel `(world_x, world_y)` lies inside the level
+    pub fn contains_world_px(&self, world_x: i64, world_y: i64) -> bool {
+        let (left, top) = self.world_px;
+        world_x >= left
+            && world_y >= top
+            && world_x < left + self.size_px.0 as i64
+            && world_y < top + self.size_px.1 as i64
+    }
+
+    /// The tile coordinates of the level under the world pixel `(world_x, world_y)`,
+    /// `None` when the pixel is outside the level
+    pub fn world_px_to_tile(&self, world_x: i64, world_y: i64) -> Option<(usize, usize)> {
+        if !self.contains_world_px(world_x, world_y) || self.grid_size_px == 0 {
+            return None;
+        }
+        let x = (world_x - self.world_px.0) as usize / self.grid_size_px;
+        let y = (world_y - self.world_px.1) as usize / self.grid_size_px;
+        self.in_bounds(x, y).then_some((x, y))
+    }
+}
+
+impl DesignMap {
+    /// Every level at `depth` containing the world pixel `(world_x, world_y)`, the one drawn on top first.
+    ///
+    /// The depth is the `world_depth` LDtk gives levels, each depth being a separate floor of the
+    /// world. Levels at the same depth are drawn in the order of the ldtk file so the later one is
+    /// on top like in LDtk.
+    pub fn levels_at(&self, world_x: i64, world_y: i64, depth: i64) -> Vec<&DesignLevel> {
+        let mut levels: Vec<_> = self
+            .levels
+            .values()
+            .filter(|level| level.world_depth == depth)
+            .filter(|level| level.contains_world_px(world_x, world_y))
+            .collect();
+        levels.sort_by_key(|level| Reverse(level.order));
+        levels
+    }
+
+    /// The top level at `depth` containing the world pixel `(world_x, world_y)`, see `levels_at`
+    pub fn level_at(&self, world_x: i64, world_y: i64, depth: i64) -> Option<&DesignLevel> {
+        self.levels_at(world_x, world_y, depth).into_iter().next()
+    }
+
+    /// The tile under the world pixel `(world_x, world_y)` in the top level at `depth`,
+    /// along with the level and the tile coordinates inside it
+    pub fn tile_at_world(
+        &self,
+        world_x: i64,
+        world_y: i64,
+        depth: i64,
+    ) -> Option<(&DesignLevel, (usize, usize), &TileContents)> {
+        let level = self.level_at(world_x, world_y, depth)?;
+        let (x, y) = level.world_px_to_tile(world_x, world_y)?;
+        Some((level, (x, y), level.get(x, y)?))
+    }
+
+    /// The tile at the world tile coordinates `(world_tile_x, world_tile_y)` in the top level at
+    /// `depth`, along with the level and the tile coordinates inside it.
+    ///
+    /// World tile coordinates are world pixels divided by the grid size of the level, so they
+    /// line up across levels as long as the levels share a grid size.
+    pub fn tile_at_world_tile(
+        &self,
+        world_tile_x: i64,
+        world_tile_y: i64,
+        depth: i64,
+    ) -> Option<(&DesignLevel, (usize, usize), &TileContents)> {
+        let mut levels: Vec<_> = self
+            .levels
+            .values()
+            .filter(|level| level.world_depth == depth)
+            .collect();
+        levels.sort_by_key(|level| Reverse(level.order));
+        levels.into_iter().find_map(|level| {
+            let grid_size = level.grid_size_px as i64;
+            let (x, y) =
+                level.world_px_to_tile(world_tile_x * grid_size, world_tile_y * grid_size)?;
+            Some((level, (x, y), level.get(x, y)?))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        design_map::DesignMap, project_builder::LevelBuilder, test_support::forest_project,
+    };
+
+    fn overlapping_world() -> DesignMap {
+        forest_project(8, &[])
+            .level(LevelBuilder::new("Left", (32, 16)).tile("Ground", (3, 1), 0))
+            .level(LevelBuilder::new("Right", (32, 16)).world_position(32, 0))
+            .level(
+                LevelBuilder::new("Above", (16, 16))
+                    .world_position(24, 0)
+                    .world_depth(1),
+            )
+            .level(LevelBuilder::new("Later", (16, 16)).world_position(48, 0))
+            .build()
+    }
+
+    #[test]
+    fn test_level_at() {
+        let world = overlapping_world();
+
+        assert_eq!(world.level_at(0, 0, 0).map(|l| l.name()), Some("Left"));
+        assert_eq!(world.level_at(31, 15, 0).map(|l| l.name()), Some("Left"));
+        assert_eq!(world.level_at(31, 15, 1).map(|l| l.name()), Some("Above"));
+        assert_eq!(world.level_at(40, 0, 0).map(|l| l.name()), Some("Right"));
+        // same depth, the level later in the file is on top
+        assert_eq!(world.level_at(50, 0, 0).map(|l| l.name()), Some("Later"));
+        assert!(world.level_at(-1, 0, 0).is_none());
+        assert!(world.level_at(0, 16, 0).is_none());
+        assert!(world.level_at(0, 0, 1).is_none());
+
+        // the level on the floor above is not part of the ground floor
+        let names: Vec<_> = world.levels_at(26, 4, 0).iter().map(|l| l.name()).collect();
+        assert_eq!(names, vec!["Left"]);
+    }
+
+    #[test]
+    fn test_tile_at_world() {
+        let world = overlapping_world();
+
+        let (level, xy, tile) = world.tile_at_world(20, 12, 0).unwrap();
+        assert_eq!(level.name(), "Left");
+        assert_eq!(xy, (2, 1));
+        assert!(!tile.has_tile());
+
+        let (level, xy, _) = world.tile_at_world(33, 9, 1).unwrap();
+        assert_eq!(level.name(), "Above");
+        assert_eq!(xy, (1, 1));
+        assert!(world.tile_at_world(100, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_tile_at_world_tile() {
+        let world = overlapping_world();
+
+        let (level, xy, tile) = world.tile_at_world_tile(3, 1, 0).unwrap();
+        assert_eq!(level.name(), "Left");
+        assert_eq!(xy, (3, 1));
+        assert!(tile.has_tile());
+
+        let (level, xy, _) = world.tile_at_world_tile(4, 1, 0).unwrap();
+        assert_eq!(level.name(), "Right");
+        assert_eq!(xy, (0, 1));
+
+        let (level, xy, _) = world.tile_at_world_tile(4, 1, 1).unwrap();
+        assert_eq!(level.name(), "Above");
+        assert_eq!(xy, (1, 1));
+        assert!(world.tile_at_world_tile(-1, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_world_tiles_of_16px_grid() {
+        let world = forest_project(16, &[])
+            .level(LevelBuilder::new("Left", (64, 32)).tile("Ground", (1, 1), 0))
+            .level(
+                LevelBuilder::new("Right", (64, 32))
+                    .world_position(64, 0)
+                    .tile("Ground", (2, 0), 0),
+            )
+            .build();
+
+        let right = &world.levels()["Right"];
+        assert_eq!(right.world_xy(), (4, 0));
+        let (level, xy, tile) = world
+            .tile_at_world_tile(right.world_tile_x() + 2, right.world_tile_y(), 0)
+            .unwrap();
+        assert_eq!((level.name(), xy), ("Right", (2, 0)));
+        assert!(tile.has_tile());
+
+        let (level, xy, tile) = world.tile_at_world_tile(1, 1, 0).unwrap();
+        assert_eq!((level.name(), xy), ("Left", (1, 1)));
+        assert!(tile.has_tile());
+    }
+}
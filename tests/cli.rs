@@ -0,0 +1,155 @@
+//! Runs the `ldtk_map` binary against the test maps, checking its exit codes and output
+
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+const MAP: &str = "./tests/testmaps/two_tileatlases.ldtk";
+
+fn ldtk_map(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ldtk_map"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// A directory for the files written by a test, removed again when dropped
+struct OutDir(PathBuf);
+
+impl OutDir {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("ldtk_map_cli_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    fn file(&self, name: &str) -> String {
+        self.0.join(name).display().to_string()
+    }
+}
+
+impl Drop for OutDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_info() {
+    let output = ldtk_map(&["info", MAP]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.starts_with("2 levels\n"), "{}", out);
+    assert!(out.contains("Level_1"), "{}", out);
+    assert!(out.contains("tileset SecondTileset"), "{}", out);
+}
+
+#[test]
+fn test_validate() {
+    let output = ldtk_map(&["validate", MAP]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("is valid"));
+
+    let output = ldtk_map(&["validate", "./tests/testmaps/bad_names.ldtk"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("error: Level_0: 2 levels share this name"));
+}
+
+#[test]
+fn test_validate_deny_warnings() {
+    let map = "./tests/testmaps/different_level_sizes.ldtk";
+    let output = ldtk_map(&["validate", map]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("warning: tileset Internal_Icons"));
+
+    let output = ldtk_map(&["validate", "--deny-warnings", map]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_dump() {
+    let output = ldtk_map(&["dump", MAP, "Level_0"]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert_eq!(out.lines().count(), 32);
+    assert!(out.lines().all(|line| line.chars().count() == 32));
+
+    let output = ldtk_map(&["dump", MAP, "Level_9"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("does not have a level named Level_9"));
+}
+
+#[test]
+fn test_diff() {
+    let output = ldtk_map(&["diff", MAP, MAP]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "No changes");
+
+    let output = ldtk_map(&["diff", MAP, "./tests/testmaps/different_level_sizes.ldtk"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Level_2: level added"));
+}
+
+#[test]
+fn test_convert() {
+    let out = OutDir::new("convert");
+    let output = ldtk_map(&["convert", MAP, &out.file("world.ldtk")]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = ldtk_map(&["info", &out.file("world.ldtk")]);
+    assert!(stdout(&output).starts_with("2 levels\n"));
+
+    let output = ldtk_map(&["convert", MAP, &out.file("world.tmj")]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(std::path::Path::new(&out.file("world_Level_0.tmj")).exists());
+    assert!(std::path::Path::new(&out.file("world_Level_1.tmj")).exists());
+
+    let output = ldtk_map(&["convert", MAP, &out.file("level.tmx"), "--level", "Level_1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(std::fs::read_to_string(out.file("level.tmx"))
+        .unwrap()
+        .contains("<map"));
+
+    let output = ldtk_map(&["convert", MAP, &out.file("world.txt")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Can not convert to .txt files"));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_convert_png() {
+    let out = OutDir::new("png");
+    let output = ldtk_map(&["convert", MAP, &out.file("level.png"), "--level", "Level_0"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        image::image_dimensions(out.file("level.png")).unwrap(),
+        (256, 256)
+    );
+}
+
+#[test]
+fn test_bake() {
+    let out = OutDir::new("bake");
+    let output = ldtk_map(&["bake", MAP, &out.file("baked.ldtk")]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("Wrote"));
+    let output = ldtk_map(&["info", &out.file("baked.ldtk")]);
+    assert!(stdout(&output).starts_with("2 levels\n"));
+
+    let output = ldtk_map(&[
+        "bake",
+        "./tests/testmaps/bad_names.ldtk",
+        &out.file("bad.ldtk"),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!std::path::Path::new(&out.file("bad.ldtk")).exists());
+}
@@ -0,0 +1,41 @@
+//! Compiles the code generated for a project full of names that are Rust keywords
+
+use ldtk_map::prelude::*;
+
+const MAP: &str = "./tests/testmaps/keywords.ldtk";
+const GENERATED: &str = "./tests/codegen/keywords.rs";
+
+mod ids {
+    include!("codegen/keywords.rs");
+}
+
+#[test]
+fn test_generated_code_is_current() {
+    let source = ldtk_map::codegen::generate(MAP);
+    if std::env::var_os("LDTK_MAP_BLESS").is_some() {
+        std::fs::write(GENERATED, &source).unwrap();
+    }
+    assert_eq!(
+        source,
+        std::fs::read_to_string(GENERATED).unwrap(),
+        "the generated code changed, rerun with LDTK_MAP_BLESS=1 to update {}",
+        GENERATED
+    );
+}
+
+#[test]
+fn test_keyword_names() {
+    assert_eq!(ids::LevelId::Self_.name(), "self");
+    assert_eq!(ids::LevelId::Type.name(), "type");
+    assert_eq!(ids::EntityId::Crate.name(), "crate");
+    assert_eq!("Self".parse(), Ok(ids::EntityId::Self_));
+    assert_eq!(ids::int_grid::mod_::GEN, 1);
+    assert_eq!(ids::int_grid::mod_::SELF, 3);
+
+    // the LDtk enum named like a generated type gets another name
+    assert_eq!(ids::LayerId2::Abstract.name(), "abstract");
+    let names: Vec<&str> = ids::Self_::ALL.iter().map(|value| value.name()).collect();
+    assert_eq!(names, ["Self", "type", "gen"]);
+    let map = DesignMap::load(MAP);
+    assert_eq!(map.enum_def("self").unwrap().check_variants(&names), Ok(()));
+}
@@ -0,0 +1,268 @@
+// Generated by ldtk_map::codegen from ./tests/testmaps/keywords.ldtk, do not edit.
+
+/// The levels of the project, `name` being the key of `DesignMap::levels`
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LevelId {
+    /// `self`
+    Self_,
+    /// `type`
+    Type,
+    /// `Level_0`
+    Level0,
+}
+
+#[allow(dead_code)]
+impl LevelId {
+    /// Every value in the order of the LDtk project
+    pub const ALL: &'static [LevelId] = &[LevelId::Self_, LevelId::Type, LevelId::Level0];
+
+    /// The name of the value as used by ldtk_map
+    pub const fn name(self) -> &'static str {
+        match self {
+            LevelId::Self_ => "self",
+            LevelId::Type => "type",
+            LevelId::Level0 => "Level_0",
+        }
+    }
+}
+
+impl ::std::str::FromStr for LevelId {
+    type Err = ::std::string::String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "self" => ::std::result::Result::Ok(LevelId::Self_),
+            "type" => ::std::result::Result::Ok(LevelId::Type),
+            "Level_0" => ::std::result::Result::Ok(LevelId::Level0),
+            _ => ::std::result::Result::Err(::std::format!("{} is not a LevelId", s)),
+        }
+    }
+}
+
+impl ::std::fmt::Display for LevelId {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The entities of the project, `name` being the one of `TileContents::entity_name`
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntityId {
+    /// `Self`
+    Self_,
+    /// `crate`
+    Crate,
+    /// `LevelId`
+    LevelId,
+}
+
+#[allow(dead_code)]
+impl EntityId {
+    /// Every value in the order of the LDtk project
+    pub const ALL: &'static [EntityId] = &[EntityId::Self_, EntityId::Crate, EntityId::LevelId];
+
+    /// The name of the value as used by ldtk_map
+    pub const fn name(self) -> &'static str {
+        match self {
+            EntityId::Self_ => "Self",
+            EntityId::Crate => "crate",
+            EntityId::LevelId => "LevelId",
+        }
+    }
+}
+
+impl ::std::str::FromStr for EntityId {
+    type Err = ::std::string::String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "Self" => ::std::result::Result::Ok(EntityId::Self_),
+            "crate" => ::std::result::Result::Ok(EntityId::Crate),
+            "LevelId" => ::std::result::Result::Ok(EntityId::LevelId),
+            _ => ::std::result::Result::Err(::std::format!("{} is not a EntityId", s)),
+        }
+    }
+}
+
+impl ::std::fmt::Display for EntityId {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The layers of the project
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LayerId {
+    /// `Entities`
+    Entities,
+    /// `Values`
+    Values,
+    /// `mod`
+    Mod,
+    /// `Ground`
+    Ground,
+}
+
+#[allow(dead_code)]
+impl LayerId {
+    /// Every value in the order of the LDtk project
+    pub const ALL: &'static [LayerId] = &[LayerId::Entities, LayerId::Values, LayerId::Mod, LayerId::Ground];
+
+    /// The name of the value as used by ldtk_map
+    pub const fn name(self) -> &'static str {
+        match self {
+            LayerId::Entities => "Entities",
+            LayerId::Values => "Values",
+            LayerId::Mod => "mod",
+            LayerId::Ground => "Ground",
+        }
+    }
+}
+
+impl ::std::str::FromStr for LayerId {
+    type Err = ::std::string::String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "Entities" => ::std::result::Result::Ok(LayerId::Entities),
+            "Values" => ::std::result::Result::Ok(LayerId::Values),
+            "mod" => ::std::result::Result::Ok(LayerId::Mod),
+            "Ground" => ::std::result::Result::Ok(LayerId::Ground),
+            _ => ::std::result::Result::Err(::std::format!("{} is not a LayerId", s)),
+        }
+    }
+}
+
+impl ::std::fmt::Display for LayerId {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The values of the LDtk enum `self`
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Self_ {
+    /// `Self`
+    Self_,
+    /// `type`
+    Type,
+    /// `gen`
+    Gen,
+}
+
+#[allow(dead_code)]
+impl Self_ {
+    /// Every value in the order of the LDtk project
+    pub const ALL: &'static [Self_] = &[Self_::Self_, Self_::Type, Self_::Gen];
+
+    /// The name of the value as used by ldtk_map
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self_::Self_ => "Self",
+            Self_::Type => "type",
+            Self_::Gen => "gen",
+        }
+    }
+}
+
+impl ::std::str::FromStr for Self_ {
+    type Err = ::std::string::String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "Self" => ::std::result::Result::Ok(Self_::Self_),
+            "type" => ::std::result::Result::Ok(Self_::Type),
+            "gen" => ::std::result::Result::Ok(Self_::Gen),
+            _ => ::std::result::Result::Err(::std::format!("{} is not a Self_", s)),
+        }
+    }
+}
+
+impl ::std::fmt::Display for Self_ {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl ::ldtk_map::FromFieldValue for Self_ {
+    fn from_field(value: &::ldtk_map::prelude::FieldValue) -> ::std::option::Option<Self> {
+        let value = value.as_enum()?;
+        if value.enum_identifier() != "self" {
+            return ::std::option::Option::None;
+        }
+        value.parse().ok()
+    }
+}
+
+/// The values of the LDtk enum `LayerId`
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LayerId2 {
+    /// `abstract`
+    Abstract,
+}
+
+#[allow(dead_code)]
+impl LayerId2 {
+    /// Every value in the order of the LDtk project
+    pub const ALL: &'static [LayerId2] = &[LayerId2::Abstract];
+
+    /// The name of the value as used by ldtk_map
+    pub const fn name(self) -> &'static str {
+        match self {
+            LayerId2::Abstract => "abstract",
+        }
+    }
+}
+
+impl ::std::str::FromStr for LayerId2 {
+    type Err = ::std::string::String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "abstract" => ::std::result::Result::Ok(LayerId2::Abstract),
+            _ => ::std::result::Result::Err(::std::format!("{} is not a LayerId2", s)),
+        }
+    }
+}
+
+impl ::std::fmt::Display for LayerId2 {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl ::ldtk_map::FromFieldValue for LayerId2 {
+    fn from_field(value: &::ldtk_map::prelude::FieldValue) -> ::std::option::Option<Self> {
+        let value = value.as_enum()?;
+        if value.enum_identifier() != "LayerId" {
+            return ::std::option::Option::None;
+        }
+        value.parse().ok()
+    }
+}
+
+/// The named values of the IntGrid layers of the project
+#[allow(dead_code)]
+pub mod int_grid {
+    /// The values of the `Values` layer
+    pub mod values {
+        /// `wall`
+        pub const WALL: usize = 1;
+        /// `deep water`
+        pub const DEEP_WATER: usize = 2;
+    }
+    /// The values of the `mod` layer
+    pub mod mod_ {
+        /// `gen`
+        pub const GEN: usize = 1;
+        /// `fn`
+        pub const FN: usize = 2;
+        /// `self`
+        pub const SELF: usize = 3;
+    }
+}